@@ -1,25 +1,65 @@
 
-use clap::{App, Arg, ArgMatches};
+use clap::{App, AppSettings, Arg, ArgMatches};
 
 
 
 
 
 pub fn get_matches<'a>() -> ArgMatches<'a> {
+	app().get_matches()
+}
+
+
+/// Returns the description of all commands and arguments
+pub fn app<'a>() -> App<'a, 'a> {
 	App::new("Project Manager")
 		.version("1.0.0")
 		.author("Christofer N. <christofer.nolander@gmail.com>")
 		.about("Manages various workspaces and projects")
 		
+		.arg(Arg::with_name("config")
+			.help("[Optional] The directory of the global workspace list, overrides RPM_HOME")
+			.long("config")
+			.takes_value(true)
+			.value_name("directory")
+			.global(true))
+		.arg(Arg::with_name("format")
+			.help("[Optional] The output format of 'current', 'list', 'path' and 'status': tree (the default), plain or json")
+			.long("format")
+			.takes_value(true)
+			.value_name("format")
+			.possible_values(&["tree", "plain", "json"])
+			.global(true))
+		
 		.subcommand(new_item_command())
 		.subcommand(remove_item_command())
+		.subcommand(edit_item_command())
+		.subcommand(move_project_command())
 		
 		.subcommand(switch_workspace_command())
+		.subcommand(workspace_command())
 		.subcommand(display_current_workspace_command())
+		.subcommand(display_recent_command())
+		.subcommand(list_items_command())
+		.subcommand(status_command())
+		.subcommand(exec_command())
 		
 		.subcommand(open_project())
+		.subcommand(project_path_command())
+		.subcommand(shell_init_command())
+		.subcommand(editor_command())
+		
+		.subcommand(adopt_command())
+		.subcommand(clone_command())
+		.subcommand(scan_command())
+		.subcommand(doctor_command())
+		.subcommand(tui_command())
+		.subcommand(trash_command())
+		
+		.subcommand(template_command())
 		
-		.get_matches()
+		.subcommand(completions_command())
+		.subcommand(complete_names_command())
 }
 
 
@@ -40,6 +80,71 @@ fn new_item_command<'a>() -> App<'a, 'a> {
 			.takes_value(true)
 			.value_name("path")
 			.required(false))
+		.arg(Arg::with_name("template")
+			.help("[Optional] The template to create a new project from (see 'rpm template list')")
+			.short("t")
+			.long("template")
+			.takes_value(true)
+			.value_name("name")
+			.required(false))
+		.arg(Arg::with_name("git")
+			.help("[Optional] Creates a git repository with a default .gitignore in the new project")
+			.long("git")
+			.required(false))
+}
+
+
+fn edit_item_command<'a>() -> App<'a, 'a> {
+	App::new("edit")
+		.about("Edits the metadata of an item ([project])")
+		.arg(Arg::with_name("type")
+			.help("Type of item to edit ([project])")
+			.required(true)
+			)
+		.arg(Arg::with_name("name")
+			.help("The name of the item")
+			.required(true)
+			)
+		.arg(Arg::with_name("description")
+			.help("[Optional] Sets the description, an empty text removes it")
+			.short("d")
+			.long("description")
+			.takes_value(true)
+			.value_name("text"))
+		.arg(Arg::with_name("tag")
+			.help("[Optional] Adds a tag")
+			.long("tag")
+			.takes_value(true)
+			.multiple(true)
+			.number_of_values(1)
+			.value_name("tag"))
+		.arg(Arg::with_name("untag")
+			.help("[Optional] Removes a tag")
+			.long("untag")
+			.takes_value(true)
+			.multiple(true)
+			.number_of_values(1)
+			.value_name("tag"))
+		.arg(Arg::with_name("project type")
+			.help("[Optional] Sets the type of project, e.g. 'rust', 'music' or 'web', an empty text removes it")
+			.long("type")
+			.takes_value(true)
+			.value_name("type"))
+		.arg(Arg::with_name("priority")
+			.help("[Optional] Sets the priority, higher is more important")
+			.long("priority")
+			.takes_value(true)
+			.allow_hyphen_values(true)
+			.value_name("priority"))
+		.arg(Arg::with_name("clear priority")
+			.help("[Optional] Removes the priority")
+			.long("clear-priority")
+			.conflicts_with("priority"))
+		.arg(Arg::with_name("remote")
+			.help("[Optional] Sets the url of the project's git repository, an empty text removes it")
+			.long("remote")
+			.takes_value(true)
+			.value_name("url"))
 }
 
 
@@ -52,6 +157,57 @@ fn switch_workspace_command<'a>() -> App<'a, 'a> {
 			)
 }
 
+fn workspace_command<'a>() -> App<'a, 'a> {
+	App::new("workspace")
+		.about("Renames, moves or clones a workspace")
+		.subcommand(App::new("rename")
+			.about("Renames a workspace")
+			.arg(Arg::with_name("name")
+				.help("The name of the workspace")
+				.required(true)
+				)
+			.arg(Arg::with_name("new name")
+				.help("The new name of the workspace")
+				.required(true)
+				)
+			)
+		.subcommand(App::new("move")
+			.about("Moves the folder of a workspace, including all projects")
+			.arg(Arg::with_name("name")
+				.help("The name of the workspace")
+				.required(true)
+				)
+			.arg(Arg::with_name("path")
+				.help("The new folder of the workspace, which must not exist")
+				.required(true)
+				)
+			)
+		.subcommand(App::new("clone")
+			.about("Creates a new workspace with the projects of a workspace")
+			.arg(Arg::with_name("name")
+				.help("The name of the workspace")
+				.required(true)
+				)
+			.arg(Arg::with_name("new name")
+				.help("The name of the new workspace")
+				.required(true)
+				)
+			.arg(Arg::with_name("path")
+				.help("[Optional] The directory to create the new workspace in")
+				.short("d")
+				.long("directory")
+				.takes_value(true)
+				.value_name("path")
+				.required(false))
+			.arg(Arg::with_name("projects")
+				.help("[Optional] Copy the contents of the project folders, not only the project database")
+				.short("p")
+				.long("projects")
+				.required(false))
+			)
+}
+
+
 fn display_current_workspace_command<'a>() -> App<'a, 'a> {
 	App::new("current")
 		.about("Displays the name of the active workspace")
@@ -64,6 +220,74 @@ fn display_current_workspace_command<'a>() -> App<'a, 'a> {
 }
 
 
+fn list_items_command<'a>() -> App<'a, 'a> {
+	App::new("list")
+		.about("Lists all items of a type ([workspaces] or [projects])")
+		.arg(Arg::with_name("type")
+			.help("Type of items to list ([workspaces] or [projects])")
+			.required(true)
+			)
+		.arg(Arg::with_name("json")
+			.help("Prints the list as JSON, the same as '--format json'")
+			.long("json")
+			)
+}
+
+
+fn status_command<'a>() -> App<'a, 'a> {
+	App::new("status")
+		.about("Displays the git status of every project in the current workspace, '--format plain' only lists the projects with uncommitted changes or unpushed commits")
+}
+
+
+fn exec_command<'a>() -> App<'a, 'a> {
+	App::new("exec")
+		.about("Runs a shell command in the folder of every project in the current workspace")
+		.arg(Arg::with_name("filter")
+			.help("[Optional] Only runs the command in projects matching 'tag:<tag>', 'type:<type>' or 'name:<project>' (including its subprojects). Projects must match every filter")
+			.short("f")
+			.long("filter")
+			.takes_value(true)
+			.multiple(true)
+			.number_of_values(1)
+			.value_name("filter"))
+		.arg(Arg::with_name("parallel")
+			.help("[Optional] The number of projects to run the command in at the same time")
+			.short("j")
+			.long("parallel")
+			.takes_value(true)
+			.value_name("N")
+			.default_value("1"))
+		.arg(Arg::with_name("command")
+			.help("The command to run, after '--'. A single argument is run by the shell, such as 'git pull && cargo build'")
+			.required(true)
+			.multiple(true)
+			.last(true))
+}
+
+
+fn display_recent_command<'a>() -> App<'a, 'a> {
+	App::new("recent")
+		.about("Displays the most recently used workspaces and projects")
+		.arg(Arg::with_name("workspaces")
+			.help("Only displays workspaces")
+			.long("workspaces")
+			.conflicts_with("projects")
+			)
+		.arg(Arg::with_name("projects")
+			.help("Only displays projects")
+			.long("projects")
+			)
+		.arg(Arg::with_name("count")
+			.help("The number of entries to display")
+			.short("n")
+			.takes_value(true)
+			.value_name("N")
+			.default_value("10")
+			)
+}
+
+
 fn remove_item_command<'a>() -> App<'a, 'a> {
 	App::new("remove")
 		.about("Removes an item ([workspace] or [project])")
@@ -75,14 +299,239 @@ fn remove_item_command<'a>() -> App<'a, 'a> {
 			.help("The name of the item")
 			.required(true)
 			)
+		.arg(Arg::with_name("delete files")
+			.help("[Optional] Move a project's folder to the workspace's trash (see 'rpm trash'), or delete a workspace's folder")
+			.long("delete-files")
+			.required(false))
+		.arg(Arg::with_name("keep files")
+			.help("[Optional] Leave the folder on disk, the default")
+			.long("keep-files")
+			.conflicts_with("delete files")
+			.required(false))
+		.arg(Arg::with_name("dry run")
+			.help("[Optional] Only display what would be removed")
+			.long("dry-run")
+			.required(false))
+}
+
+
+fn move_project_command<'a>() -> App<'a, 'a> {
+	App::new("mv")
+		.about("Renames a project or moves it to another parent project, together with its folder")
+		.arg(Arg::with_name("from")
+			.help("The name of the project")
+			.required(true)
+			)
+		.arg(Arg::with_name("to")
+			.help("The new name of the project, such as 'archive:music:demos'")
+			.required(true)
+			)
+}
+
+
+fn project_path_command<'a>() -> App<'a, 'a> {
+	App::new("path")
+		.about("Displays the absolute path to a project's folder")
+		.arg(Arg::with_name("name")
+			.help("The name of the project")
+			.required(true)
+			)
+}
+
+
+fn shell_init_command<'a>() -> App<'a, 'a> {
+	App::new("shell-init")
+		.about("Prints the shell function 'rcd', which changes to the folder of a project. Add 'eval \"$(rpm shell-init bash)\"' to ~/.bashrc, the same for zsh, or 'rpm shell-init fish | source' to the fish config")
+		.arg(Arg::with_name("shell")
+			.help("The shell to print the function for")
+			.required(true)
+			.possible_values(&["bash", "zsh", "fish"])
+			)
 }
 
 
 fn open_project<'a>() -> App<'a, 'a> {
 	App::new("open")
-		.about("Opens a project in the file explorer")
+		.about("Opens a project in the configured editor (see 'rpm editor')")
 		.arg(Arg::with_name("name")
 			.help("The name of the project")
 			.required(true)
 			)
-}
\ No newline at end of file
+		.arg(Arg::with_name("with")
+			.help("[Optional] Open the project with this command instead, e.g. 'code {path}'")
+			.short("w")
+			.long("with")
+			.takes_value(true)
+			.value_name("command")
+			.required(false))
+}
+
+
+fn editor_command<'a>() -> App<'a, 'a> {
+	App::new("editor")
+		.about("Displays or sets the command used to open projects, e.g. 'code {path}' or '$EDITOR {path}'")
+		.arg(Arg::with_name("command")
+			.help("[Optional] The new editor command, the project path replaces '{path}' or is appended")
+			.required(false)
+			)
+		.arg(Arg::with_name("workspace")
+			.help("Applies to the current workspace instead of globally")
+			.long("workspace")
+			.conflicts_with("project")
+			)
+		.arg(Arg::with_name("project")
+			.help("Applies to a single project instead of globally")
+			.short("p")
+			.long("project")
+			.takes_value(true)
+			.value_name("name")
+			)
+		.arg(Arg::with_name("unset")
+			.help("Removes the editor setting")
+			.long("unset")
+			.conflicts_with("command")
+			)
+}
+
+
+fn adopt_command<'a>() -> App<'a, 'a> {
+	App::new("adopt")
+		.about("Adds an existing directory to the current workspace as a project")
+		.arg(Arg::with_name("directory")
+			.help("The directory to add")
+			.required(true)
+			)
+		.arg(Arg::with_name("name")
+			.help("[Optional] The name of the project, defaults to the name of the directory")
+			.long("as")
+			.takes_value(true)
+			.value_name("name")
+			.required(false))
+		.arg(Arg::with_name("link")
+			.help("[Optional] Link to the directory instead of moving it into the workspace")
+			.short("l")
+			.long("link")
+			.required(false))
+}
+
+
+fn clone_command<'a>() -> App<'a, 'a> {
+	App::new("clone")
+		.about("Clones a git repository into a new project in the current workspace")
+		.arg(Arg::with_name("url")
+			.help("The url or path of the repository")
+			.required(true)
+			)
+		.arg(Arg::with_name("name")
+			.help("[Optional] The name of the project, such as 'music:synth', defaults to the name of the repository")
+			.required(false)
+			)
+}
+
+
+fn scan_command<'a>() -> App<'a, 'a> {
+	App::new("scan")
+		.about("Adds every directory in the projects folder of the current workspace which is not a project yet")
+		.arg(Arg::with_name("yes")
+			.help("[Optional] Add the directories without asking for confirmation")
+			.short("y")
+			.long("yes")
+			.required(false))
+}
+
+
+fn doctor_command<'a>() -> App<'a, 'a> {
+	App::new("doctor")
+		.about("Checks the workspaces and projects for inconsistencies between the databases and the disk")
+		.arg(Arg::with_name("fix")
+			.help("[Optional] Repair the problems that were found")
+			.short("f")
+			.long("fix")
+			.required(false))
+}
+
+
+fn tui_command<'a>() -> App<'a, 'a> {
+	App::new("tui")
+		.about("Browses the workspaces and projects in a full screen terminal interface")
+}
+
+
+fn trash_command<'a>() -> App<'a, 'a> {
+	App::new("trash")
+		.about("Manages the projects removed from the current workspace with '--delete-files'")
+		.subcommand(App::new("list")
+			.about("Lists the removed projects")
+			)
+		.subcommand(App::new("restore")
+			.about("Moves a removed project back to where it was")
+			.arg(Arg::with_name("name")
+				.help("The name of the project, or its id in the trash")
+				.required(true)
+				)
+			)
+		.subcommand(App::new("empty")
+			.about("Permanently deletes all removed projects")
+			.arg(Arg::with_name("yes")
+				.help("[Optional] Delete the projects without asking for confirmation")
+				.short("y")
+				.long("yes")
+				.required(false))
+			)
+}
+
+
+fn template_command<'a>() -> App<'a, 'a> {
+	App::new("template")
+		.about("Manages the global project templates")
+		.subcommand(App::new("add")
+			.about("Copies a directory into the template store")
+			.arg(Arg::with_name("name")
+				.help("The name of the template")
+				.required(true)
+				)
+			.arg(Arg::with_name("directory")
+				.help("The directory to create the template from")
+				.required(true)
+				)
+			)
+		.subcommand(App::new("list")
+			.about("Lists all templates")
+			)
+		.subcommand(App::new("remove")
+			.about("Removes a template from the store")
+			.arg(Arg::with_name("name")
+				.help("The name of the template")
+				.required(true)
+				)
+			)
+		.subcommand(App::new("show")
+			.about("Displays the files and variables of a template")
+			.arg(Arg::with_name("name")
+				.help("The name of the template")
+				.required(true)
+				)
+			)
+}
+
+
+fn completions_command<'a>() -> App<'a, 'a> {
+	App::new("completions")
+		.about("Prints the completion script for a shell. Bash, zsh, fish and PowerShell also complete the names of workspaces and projects")
+		.arg(Arg::with_name("shell")
+			.help("The shell to print the script for")
+			.required(true)
+			.possible_values(&["bash", "zsh", "fish", "powershell"])
+			)
+}
+
+
+/// Used by the completion scripts to look up names while completing
+fn complete_names_command<'a>() -> App<'a, 'a> {
+	App::new("complete-names")
+		.setting(AppSettings::Hidden)
+		.arg(Arg::with_name("type")
+			.required(true)
+			.possible_values(&["workspaces", "projects"])
+			)
+}
@@ -0,0 +1,98 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use error::{Error, Result};
+use files;
+
+
+/// The environment variable which overrides the data directory
+pub const DATA_DIRECTORY_VARIABLE: &str = "RPM_HOME";
+
+/// The name of the folder within the platform's configuration directory
+const DATA_DIRECTORY_NAME: &str = "rpm";
+
+/// The files and folders which are moved from the executable's directory by older versions
+const MIGRATED_FILES: [&str; 5] = ["workspaces.json", "templates.json", "settings.json", "history.json", "templates"];
+
+static DATA_DIRECTORY_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+
+/// Uses a directory for the global files, taking precedence over the 'RPM_HOME' environment
+/// variable and the platform's configuration directory
+pub fn set_data_directory<P: AsRef<Path>>(path: P) {
+	*DATA_DIRECTORY_OVERRIDE.lock().unwrap() = Some(path.as_ref().to_path_buf());
+}
+
+
+/// Return the directory holding the global files shared by all workspaces, creating it if needed.
+///
+/// This is, in order of precedence, the directory passed to `set_data_directory`, the 'RPM_HOME'
+/// environment variable, or the 'rpm' folder in the platform's configuration directory. Files
+/// stored next to the executable by older versions are copied to the configuration directory the
+/// first time it is used
+pub fn data_directory() -> Result<PathBuf> {
+	let overridden = DATA_DIRECTORY_OVERRIDE.lock().unwrap().clone();
+	
+	let path = match overridden.or_else(|| env::var_os(DATA_DIRECTORY_VARIABLE).map(PathBuf::from)) {
+		Some(path) => path,
+		None => {
+			let path = default_data_directory()?;
+			if !path.join(MIGRATED_FILES[0]).exists() {
+				fs::create_dir_all(&path).map_err(|e| Error::io(&path, e))?;
+				migrate_from_executable_directory(&path)?;
+			}
+			path
+		}
+	};
+	
+	fs::create_dir_all(&path).map_err(|e| Error::io(&path, e))?;
+	Ok(path)
+}
+
+
+/// Returns the 'rpm' folder within the platform's configuration directory
+fn default_data_directory() -> Result<PathBuf> {
+	let config_directory = if cfg!(target_os = "windows") {
+		env::var_os("APPDATA").map(PathBuf::from)
+	} else {
+		match env::var_os("XDG_CONFIG_HOME") {
+			Some(ref path) if !path.is_empty() => Some(PathBuf::from(path)),
+			_ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+		}
+	};
+	
+	match config_directory {
+		Some(path) => Ok(path.join(DATA_DIRECTORY_NAME)),
+		None => Err(Error::Invalid(format!("Failed to find the configuration directory, set {} to the directory rpm should use", DATA_DIRECTORY_VARIABLE))),
+	}
+}
+
+
+/// Copies the global files from the executable's directory, where older versions stored them
+fn migrate_from_executable_directory(destination: &Path) -> Result<()> {
+	let mut source = match env::current_exe() {
+		Ok(path) => path,
+		Err(_) => return Ok(()),
+	};
+	source.pop();
+	
+	if !source.join(MIGRATED_FILES[0]).exists() {
+		return Ok(());
+	}
+	
+	for name in MIGRATED_FILES.iter() {
+		let from = source.join(name);
+		let to = destination.join(name);
+		
+		if from.is_dir() {
+			files::copy_folder(&from, &to)?;
+		} else if from.is_file() {
+			fs::copy(&from, &to).map_err(|e| Error::io(&to, e))?;
+		}
+	}
+	
+	Ok(())
+}
+
@@ -0,0 +1,356 @@
+use std::fs;
+use std::io::{self, Write, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+
+use error::{Error, Result};
+
+
+/// How long to wait for another process to release a database
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Locks older than this are left behind by crashed processes, if they do not name the process
+/// holding them
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(60);
+
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+
+/// An advisory lock on a database file, held until dropped. Other processes wait for the lock
+/// before reading a database they are going to modify
+pub struct Lock {
+	path: PathBuf
+}
+
+
+impl Drop for Lock {
+	fn drop(&mut self) {
+		// The lock may have been taken over by another process if this one was considered crashed
+		if lock_owner(&self.path) == Some(process::id()) {
+			let _ = fs::remove_file(&self.path);
+		}
+	}
+}
+
+
+/// Locks a database file, waiting for other processes to release it
+pub fn lock(path: &Path) -> Result<Lock> {
+	let lock_path = sibling_path(path, "lock");
+	let start = SystemTime::now();
+	
+	loop {
+		match fs::OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+			Ok(mut file) => {
+				file.write_all(process::id().to_string().as_bytes()).map_err(|e| Error::io(&lock_path, e))?;
+				return Ok(Lock {
+					path: lock_path
+				});
+			},
+			Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => (),
+			Err(e) => return Err(Error::io(&lock_path, e)),
+		}
+		
+		let pid = lock_owner(&lock_path);
+		if is_stale(&lock_path, pid) {
+			take_over(&lock_path, pid);
+			continue;
+		}
+		
+		if start.elapsed().unwrap_or_default() >= LOCK_TIMEOUT {
+			return Err(Error::Locked {
+				path: path.to_path_buf(),
+				pid
+			});
+		}
+		
+		thread::sleep(LOCK_RETRY_INTERVAL);
+	}
+}
+
+
+/// Removes a stale lock. The lock is first moved aside, so only one of several waiting processes
+/// gets it. If another process took it over in the meantime, its new lock is put back
+fn take_over(lock_path: &Path, pid: Option<u32>) {
+	let aside = sibling_path(lock_path, &format!("{}.stale", process::id()));
+	if fs::rename(lock_path, &aside).is_err() {
+		return;
+	}
+	
+	let owner = lock_owner(&aside);
+	if owner != pid || !is_stale(&aside, owner) {
+		// Linking fails if yet another process has locked the database in the meantime
+		let _ = fs::hard_link(&aside, lock_path);
+	}
+	
+	let _ = fs::remove_file(&aside);
+}
+
+
+/// Returns the id of the process holding a lock
+fn lock_owner(lock_path: &Path) -> Option<u32> {
+	let mut contents = String::new();
+	fs::File::open(lock_path).ok()?.read_to_string(&mut contents).ok()?;
+	contents.trim().parse().ok()
+}
+
+
+/// Returns true if a lock was left behind by a process that is no longer running. A lock held by
+/// a running process is never stale, however long it is held
+fn is_stale(lock_path: &Path, pid: Option<u32>) -> bool {
+	if let Some(pid) = pid {
+		return !is_running(pid);
+	}
+	
+	match fs::metadata(lock_path).and_then(|metadata| metadata.modified()) {
+		Ok(modified) => modified.elapsed().unwrap_or_default() > LOCK_STALE_AFTER,
+		Err(_) => false,
+	}
+}
+
+
+/// Returns true if a process is running, or if that can not be found out
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+	use libc;
+	
+	// Signal 0 only checks whether the process exists and may be signalled
+	if unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+		return true;
+	}
+	io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+
+/// Returns true if a process is running, or if that can not be found out
+#[cfg(not(unix))]
+fn is_running(pid: u32) -> bool {
+	use std::process::{Command, Stdio};
+	
+	let output = Command::new("tasklist").args(&["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"]).stderr(Stdio::null()).output();
+	match output {
+		Ok(ref output) if output.status.success() => String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)),
+		_ => true,
+	}
+}
+
+
+/// Returns true if a database file, or a backup of it, exists
+pub fn exists(path: &Path) -> bool {
+	path.exists() || backup_path(path).exists()
+}
+
+
+/// Returns an error if a database file does not contain valid JSON, without restoring its backup
+pub fn check(path: &Path) -> Result<()> {
+	read::<serde_json::Value>(path).map(|_| ())
+}
+
+
+/// Returns true if a database file has a backup containing valid JSON
+pub fn has_backup(path: &Path) -> bool {
+	read::<serde_json::Value>(&backup_path(path)).is_ok()
+}
+
+
+/// Reads and deserializes a JSON file. If the file is missing or does not parse, but its backup
+/// does, the backup is restored and the broken file is kept with the extension '.corrupt'
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T> {
+	let error = match read(path) {
+		Ok(value) => return Ok(value),
+		Err(error) => error,
+	};
+	
+	let restorable = match error {
+		Error::Parse { .. } => true,
+		Error::Io { ref source, .. } => source.kind() == io::ErrorKind::NotFound,
+		_ => false,
+	};
+	if !restorable {
+		return Err(error);
+	}
+	
+	let backup = backup_path(path);
+	let value = match read(&backup) {
+		Ok(value) => value,
+		Err(_) => return Err(error),
+	};
+	
+	if path.exists() {
+		let corrupt = sibling_path(path, "corrupt");
+		fs::rename(path, &corrupt).map_err(|e| Error::io(path, e))?;
+	}
+	
+	let temporary = sibling_path(path, "tmp");
+	fs::copy(&backup, &temporary).map_err(|e| Error::io(&temporary, e))?;
+	replace(&temporary, path)?;
+	
+	Ok(value)
+}
+
+
+/// Serializes a value and writes it to a JSON file without ever leaving a partially written file.
+/// The previous version of the file is kept with the extension '.bak'
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+	let contents = serde_json::to_string_pretty(value).unwrap();
+	
+	// Write the new version next to the old one
+	let temporary = sibling_path(path, "tmp");
+	{
+		let mut file = fs::File::create(&temporary).map_err(|e| Error::io(&temporary, e))?;
+		file.write_all(contents.as_bytes()).map_err(|e| Error::io(&temporary, e))?;
+		file.sync_all().map_err(|e| Error::io(&temporary, e))?;
+	}
+	
+	// Only keep a backup of versions that can be restored
+	if read::<serde_json::Value>(path).is_ok() {
+		let backup = backup_path(path);
+		fs::copy(path, &backup).map_err(|e| Error::io(&backup, e))?;
+		fs::OpenOptions::new().write(true).open(&backup)
+			.and_then(|file| file.sync_all())
+			.map_err(|e| Error::io(&backup, e))?;
+	}
+	
+	replace(&temporary, path)
+}
+
+
+/// Reads and deserializes a JSON file
+fn read<T: DeserializeOwned>(path: &Path) -> Result<T> {
+	let mut contents = String::new();
+	
+	let mut file = fs::File::open(path).map_err(|e| Error::io(path, e))?;
+	file.read_to_string(&mut contents).map_err(|e| Error::io(path, e))?;
+	
+	serde_json::from_str(&contents).map_err(|e| Error::parse(path, e))
+}
+
+
+/// Atomically replaces a file with another one
+fn replace(source: &Path, destination: &Path) -> Result<()> {
+	fs::rename(source, destination).map_err(|e| Error::io(destination, e))?;
+	
+	// Make sure the rename itself survives a crash, folders can not be opened on every platform
+	if let Some(parent) = destination.parent() {
+		if let Ok(folder) = fs::File::open(parent) {
+			let _ = folder.sync_all();
+		}
+	}
+	
+	Ok(())
+}
+
+
+/// Returns the path to the backup of a database file
+fn backup_path(path: &Path) -> PathBuf {
+	sibling_path(path, "bak")
+}
+
+
+/// Returns the path of a file with an extra extension, 'projects.json' becomes 'projects.json.bak'
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+	let mut name = path.file_name().unwrap_or_default().to_os_string();
+	name.push(".");
+	name.push(extension);
+	path.with_file_name(name)
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::env;
+	
+	
+	/// Returns the path to a database file in a new, empty temporary folder
+	fn database(name: &str) -> PathBuf {
+		let folder = env::temp_dir().join(format!("rpm-test-database-{}-{}", name, process::id()));
+		let _ = fs::remove_dir_all(&folder);
+		fs::create_dir_all(&folder).unwrap();
+		folder.join("projects.json")
+	}
+	
+	
+	fn version(number: u64) -> serde_json::Value {
+		serde_json::from_str(&format!("{{ \"version\": {} }}", number)).unwrap()
+	}
+	
+	
+	#[test]
+	fn save_keeps_the_previous_version_as_backup() {
+		let path = database("save");
+		save(&path, &version(1)).unwrap();
+		assert!(!backup_path(&path).exists());
+		
+		save(&path, &version(2)).unwrap();
+		assert_eq!(read::<serde_json::Value>(&path).unwrap(), version(2));
+		assert_eq!(read::<serde_json::Value>(&backup_path(&path)).unwrap(), version(1));
+		assert!(!sibling_path(&path, "tmp").exists());
+		
+		fs::remove_dir_all(path.parent().unwrap()).unwrap();
+	}
+	
+	
+	#[test]
+	fn save_does_not_back_up_a_corrupt_file() {
+		let path = database("corrupt-save");
+		save(&path, &version(1)).unwrap();
+		save(&path, &version(2)).unwrap();
+		fs::write(&path, "{ \"version\": ").unwrap();
+		
+		save(&path, &version(3)).unwrap();
+		assert_eq!(read::<serde_json::Value>(&backup_path(&path)).unwrap(), version(1));
+		
+		fs::remove_dir_all(path.parent().unwrap()).unwrap();
+	}
+	
+	
+	#[test]
+	fn load_restores_the_backup_of_a_corrupt_file() {
+		let path = database("restore");
+		save(&path, &version(1)).unwrap();
+		save(&path, &version(2)).unwrap();
+		fs::write(&path, "{ \"version\": ").unwrap();
+		
+		assert_eq!(load::<serde_json::Value>(&path).unwrap(), version(1));
+		assert_eq!(read::<serde_json::Value>(&path).unwrap(), version(1));
+		assert_eq!(fs::read_to_string(sibling_path(&path, "corrupt")).unwrap(), "{ \"version\": ");
+		
+		fs::remove_dir_all(path.parent().unwrap()).unwrap();
+	}
+	
+	
+	#[test]
+	fn load_restores_the_backup_of_a_missing_file() {
+		let path = database("missing");
+		save(&path, &version(1)).unwrap();
+		save(&path, &version(2)).unwrap();
+		fs::remove_file(&path).unwrap();
+		
+		assert!(exists(&path));
+		assert_eq!(load::<serde_json::Value>(&path).unwrap(), version(1));
+		assert!(path.exists());
+		
+		fs::remove_dir_all(path.parent().unwrap()).unwrap();
+	}
+	
+	
+	#[test]
+	fn load_fails_without_a_backup() {
+		let path = database("no-backup");
+		fs::write(&path, "not json").unwrap();
+		
+		match load::<serde_json::Value>(&path) {
+			Err(Error::Parse { .. }) => (),
+			_ => panic!("a corrupt file without a backup should not load"),
+		}
+		assert!(path.exists());
+		
+		fs::remove_dir_all(path.parent().unwrap()).unwrap();
+	}
+}
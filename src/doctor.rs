@@ -0,0 +1,286 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json;
+
+use config::data_directory;
+use database;
+use error::{Error, Result};
+use workspace::{Workspace, WorkspaceList};
+
+
+/// An inconsistency between the databases and the file system
+pub enum Problem {
+	/// A database file does not contain valid JSON
+	CorruptDatabase {
+		/// The database file
+		path: PathBuf,
+		/// The reason the file could not be read
+		message: String,
+		/// True if the file has a valid backup
+		restorable: bool
+	},
+	
+	/// The folder of a workspace no longer exists
+	MissingWorkspaceFolder {
+		/// The name of the workspace
+		workspace: String,
+		/// The path of the missing folder
+		path: String
+	},
+	
+	/// The active workspace is not in the list of workspaces
+	MissingCurrentWorkspace {
+		/// The name of the active workspace
+		name: String
+	},
+	
+	/// Several workspaces have names which only differ by case
+	DuplicateWorkspaces {
+		/// The names of the workspaces
+		names: Vec<String>
+	},
+	
+	/// The project database of a workspace does not exist
+	MissingProjectDatabase {
+		/// The workspace
+		workspace: Workspace
+	},
+	
+	/// A project's folder does not exist
+	MissingProjectFolder {
+		/// The workspace containing the project
+		workspace: Workspace,
+		/// The name of the project, such as 'project1:project11'
+		project: String
+	},
+	
+	/// A folder in the projects folder does not belong to a project
+	UntrackedFolder {
+		/// The workspace containing the folder
+		workspace: Workspace,
+		/// The name of the folder
+		folder: String
+	},
+	
+	/// Several projects in a workspace have names which only differ by case
+	DuplicateProjects {
+		/// The name of the workspace
+		workspace: String,
+		/// The names of the projects
+		names: Vec<String>
+	}
+}
+
+
+impl Problem {
+	/// Returns true if the problem can be repaired automatically
+	pub fn is_fixable(&self) -> bool {
+		match *self {
+			Problem::CorruptDatabase { restorable, .. } => restorable,
+			// The folder of a workspace may only be unavailable for now, such as on an unmounted drive
+			Problem::MissingWorkspaceFolder { .. } => false,
+			Problem::DuplicateWorkspaces { .. } | Problem::DuplicateProjects { .. } => false,
+			_ => true,
+		}
+	}
+	
+	
+	/// Repairs the problem. Missing project folders are created again since the project's metadata
+	/// is still intact. Workspaces whose folder is missing are kept, they have to be removed with
+	/// 'rpm remove workspace' once their folder is gone for good
+	pub fn fix(&self) -> Result<()> {
+		match *self {
+			Problem::CorruptDatabase { ref path, restorable: true, .. } =>
+				database::load::<serde_json::Value>(path).map(|_| ()),
+			
+			Problem::MissingCurrentWorkspace { .. } =>
+				WorkspaceList::update(|list| {
+					list.clear_current();
+					Ok(())
+				}),
+			
+			Problem::MissingProjectDatabase { ref workspace } =>
+				workspace.create_preferences(),
+			
+			Problem::MissingProjectFolder { ref workspace, ref project } =>
+				workspace.create_project_folder(project),
+			
+			Problem::UntrackedFolder { ref workspace, ref folder } =>
+				workspace.clone().register_project(folder),
+			
+			_ => Err(Error::Invalid(format!("{} can not be repaired automatically", self))),
+		}
+	}
+}
+
+
+impl fmt::Display for Problem {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Problem::CorruptDatabase { ref path, ref message, restorable } => {
+				write!(f, "'{}' is corrupt: {}", path.display(), message)?;
+				if !restorable {
+					write!(f, " (no backup)")?;
+				}
+				Ok(())
+			},
+			
+			Problem::MissingWorkspaceFolder { ref workspace, ref path } =>
+				write!(f, "The folder of workspace '{}' is missing: '{}'. Remove the workspace with 'rpm remove workspace {}' if it is gone for good", workspace, path, workspace),
+			
+			Problem::MissingCurrentWorkspace { ref name } =>
+				write!(f, "The active workspace '{}' does not exist", name),
+			
+			Problem::DuplicateWorkspaces { ref names } =>
+				write!(f, "Workspace names differ only by case: {}", names.join(", ")),
+			
+			Problem::MissingProjectDatabase { ref workspace } =>
+				write!(f, "The project database of workspace '{}' is missing", workspace.name()),
+			
+			Problem::MissingProjectFolder { ref workspace, ref project } =>
+				write!(f, "The folder of project '{}' in '{}' is missing", project, workspace.name()),
+			
+			Problem::UntrackedFolder { ref workspace, ref folder } =>
+				write!(f, "The folder '{}' in '{}' is not a project", folder, workspace.name()),
+			
+			Problem::DuplicateProjects { ref workspace, ref names } =>
+				write!(f, "Project names in '{}' differ only by case: {}", workspace, names.join(", ")),
+		}
+	}
+}
+
+
+/// Checks all databases, workspaces and projects for problems. Nothing is modified, databases
+/// which do not contain valid JSON are not restored and the workspaces or projects in them are
+/// not checked
+pub fn diagnose() -> Result<Vec<Problem>> {
+	let mut problems = Vec::new();
+	
+	let directory = data_directory()?;
+	let mut workspaces_corrupt = false;
+	
+	let mut entries: Vec<PathBuf> = fs::read_dir(&directory).map_err(|e| Error::io(&directory, e))?
+		.filter_map(|entry| entry.ok())
+		.map(|entry| entry.path())
+		.filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+		.collect();
+	entries.sort();
+	
+	for path in entries.iter() {
+		if let Some(problem) = check_database(path) {
+			if path.file_name().is_some_and(|name| name == "workspaces.json") {
+				workspaces_corrupt = true;
+			}
+			problems.push(problem);
+		}
+	}
+	
+	if workspaces_corrupt {
+		return Ok(problems);
+	}
+	
+	let list = WorkspaceList::get()?;
+	
+	if !list.current_name().is_empty() && list.lookup(list.current_name()).is_err() {
+		problems.push(Problem::MissingCurrentWorkspace {
+			name: list.current_name().to_owned()
+		});
+	}
+	
+	let mut names: Vec<String> = list.workspaces().iter().map(|workspace| workspace.name().to_owned()).collect();
+	names.sort_by_key(|name| name.to_lowercase());
+	for group in names.chunk_by(|a, b| a.to_lowercase() == b.to_lowercase()) {
+		if group.len() > 1 {
+			problems.push(Problem::DuplicateWorkspaces {
+				names: group.to_vec()
+			});
+		}
+	}
+	
+	for workspace in list.workspaces().iter() {
+		diagnose_workspace(workspace, &mut problems)?;
+	}
+	
+	Ok(problems)
+}
+
+
+/// Checks the projects of a workspace for problems
+fn diagnose_workspace(workspace: &Workspace, problems: &mut Vec<Problem>) -> Result<()> {
+	if !Path::new(workspace.path()).is_dir() {
+		problems.push(Problem::MissingWorkspaceFolder {
+			workspace: workspace.name().to_owned(),
+			path: workspace.path().to_owned()
+		});
+		return Ok(());
+	}
+	
+	let database_path = workspace.project_database_path();
+	if !database::exists(Path::new(&database_path)) {
+		problems.push(Problem::MissingProjectDatabase {
+			workspace: workspace.clone()
+		});
+		return Ok(());
+	}
+	
+	if let Some(problem) = check_database(Path::new(&database_path)) {
+		problems.push(problem);
+		return Ok(());
+	}
+	
+	let duplicates = workspace.get_project_list()?.get_duplicate_names();
+	
+	// The folders of duplicate projects can not be looked up until they are renamed
+	let missing: Vec<String> = workspace.get_missing_project_folders()?.into_iter()
+		.filter(|project| !duplicates.iter().any(|names| names.contains(project)))
+		.collect();
+	
+	for names in duplicates {
+		problems.push(Problem::DuplicateProjects {
+			workspace: workspace.name().to_owned(),
+			names
+		});
+	}
+	
+	for project in missing {
+		problems.push(Problem::MissingProjectFolder {
+			workspace: workspace.clone(),
+			project
+		});
+	}
+	
+	for folder in workspace.get_untracked_folders()? {
+		problems.push(Problem::UntrackedFolder {
+			workspace: workspace.clone(),
+			folder
+		});
+	}
+	
+	Ok(())
+}
+
+
+/// Returns a problem if a database file is missing or does not contain valid JSON
+fn check_database(path: &Path) -> Option<Problem> {
+	if !path.exists() {
+		return Some(Problem::CorruptDatabase {
+			path: path.to_path_buf(),
+			message: "The file is missing".to_owned(),
+			restorable: database::has_backup(path)
+		});
+	}
+	
+	match database::check(path) {
+		Ok(()) => None,
+		Err(error) => Some(Problem::CorruptDatabase {
+			path: path.to_path_buf(),
+			message: match error {
+				Error::Parse { line, column, message, .. } => format!("{} (line {}, column {})", message, line, column),
+				error => error.to_string(),
+			},
+			restorable: database::has_backup(path)
+		}),
+	}
+}
@@ -0,0 +1,204 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::result;
+
+use serde_json;
+
+
+/// The result of all fallible operations in this crate
+pub type Result<T> = result::Result<T, Error>;
+
+
+/// Everything that can go wrong while managing workspaces and projects
+#[derive(Debug)]
+pub enum Error {
+	/// No item, such as a "workspace" or "project", has the name. Contains close matches, if any
+	NotFound {
+		/// The kind of item that was looked up
+		kind: &'static str,
+		/// The name that was looked up
+		name: String,
+		/// Names of existing items resembling the name
+		suggestions: Vec<String>
+	},
+	
+	/// An item with the name already exists
+	AlreadyExists {
+		/// The kind of item
+		kind: &'static str,
+		/// The name of the existing item
+		name: String
+	},
+	
+	/// A name matches several items equally well
+	Ambiguous {
+		/// The name that was looked up
+		name: String,
+		/// The names of all matching items
+		candidates: Vec<String>
+	},
+	
+	/// Reading or writing a file or folder failed
+	Io {
+		/// The file or folder that was accessed
+		path: PathBuf,
+		/// The underlying error
+		source: io::Error
+	},
+	
+	/// A database file does not contain valid JSON
+	Parse {
+		/// The file that was read
+		path: PathBuf,
+		/// The line of the first syntax error, starting at 1
+		line: usize,
+		/// The column of the first syntax error, starting at 1
+		column: usize,
+		/// A description of the syntax error
+		message: String
+	},
+	
+	/// Another process did not release a database in time
+	Locked {
+		/// The database file
+		path: PathBuf,
+		/// The process holding the lock, if known
+		pid: Option<u32>
+	},
+	
+	/// An external program could not be run or did not succeed
+	Command {
+		/// The command line that was run
+		command: String,
+		/// What went wrong
+		message: String
+	},
+	
+	/// The request does not make sense, such as an invalid name or missing selection
+	Invalid(String)
+}
+
+
+impl Error {
+	/// Creates an error for a failed file system operation
+	pub fn io<P: AsRef<Path>>(path: P, source: io::Error) -> Error {
+		Error::Io {
+			path: path.as_ref().to_path_buf(),
+			source
+		}
+	}
+	
+	
+	/// Creates an error for a file that could not be deserialized
+	pub fn parse<P: AsRef<Path>>(path: P, source: serde_json::Error) -> Error {
+		// The position is stored separately, remove it from the message
+		let mut message = source.to_string();
+		let position = format!(" at line {} column {}", source.line(), source.column());
+		if message.ends_with(&position) {
+			let length = message.len() - position.len();
+			message.truncate(length);
+		}
+		
+		Error::Parse {
+			path: path.as_ref().to_path_buf(),
+			line: source.line(),
+			column: source.column(),
+			message
+		}
+	}
+	
+	
+	/// Creates an error for an item that does not exist
+	pub fn not_found(kind: &'static str, name: &str) -> Error {
+		Error::NotFound {
+			kind,
+			name: name.to_owned(),
+			suggestions: Vec::new()
+		}
+	}
+	
+	
+	/// Creates an error for an item that already exists
+	pub fn already_exists(kind: &'static str, name: &str) -> Error {
+		Error::AlreadyExists {
+			kind,
+			name: name.to_owned()
+		}
+	}
+	
+	
+	/// The exit code of the process when it fails with this error
+	pub fn exit_code(&self) -> i32 {
+		match *self {
+			Error::NotFound { .. } => 2,
+			Error::AlreadyExists { .. } => 3,
+			Error::Ambiguous { .. } => 4,
+			Error::Io { .. } => 5,
+			Error::Parse { .. } => 6,
+			Error::Command { .. } => 7,
+			Error::Invalid(_) => 8,
+			Error::Locked { .. } => 9,
+		}
+	}
+}
+
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::NotFound { kind, ref name, ref suggestions } => {
+				write!(f, "No {} with the name '{}' found!", kind, name)?;
+				
+				if !suggestions.is_empty() {
+					write!(f, "\nDid you mean:")?;
+					for suggestion in suggestions.iter() {
+						write!(f, "\n{}", suggestion)?;
+					}
+				}
+				
+				Ok(())
+			},
+			
+			Error::AlreadyExists { kind, ref name } =>
+				write!(f, "A {} with the name '{}' already exists!", kind, name),
+			
+			Error::Ambiguous { ref name, ref candidates } => {
+				write!(f, "'{}' matches several items:", name)?;
+				for candidate in candidates.iter() {
+					write!(f, "\n{}", candidate)?;
+				}
+				Ok(())
+			},
+			
+			Error::Io { ref path, ref source } =>
+				write!(f, "Failed to access '{}': {}", path.display(), source),
+			
+			Error::Parse { ref path, line, column, ref message } =>
+				write!(f, "Failed to load '{}' (line {}, column {}): {}", path.display(), line, column, message),
+			
+			Error::Locked { ref path, pid: Some(pid) } =>
+				write!(f, "The database '{}' is locked by pid {}", path.display(), pid),
+			
+			Error::Locked { ref path, pid: None } =>
+				write!(f, "The database '{}' is locked by another process", path.display()),
+			
+			Error::Command { ref command, ref message } =>
+				write!(f, "'{}' failed: {}", command, message),
+			
+			Error::Invalid(ref message) =>
+				write!(f, "{}", message),
+		}
+	}
+}
+
+
+impl error::Error for Error {
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match *self {
+			Error::Io { ref source, .. } => Some(source),
+			_ => None,
+		}
+	}
+}
@@ -0,0 +1,177 @@
+use std::cmp;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use rpm::{Error, Result, Workspace};
+use rpm::project::ProjectFilter;
+
+
+/// How running the command in a project ended
+enum Outcome {
+	/// The command ran and exited
+	Exited(ExitStatus),
+	/// The shell could not be started
+	Failed(io::Error),
+	/// The project's folder does not exist
+	Missing
+}
+
+
+impl Outcome {
+	fn is_success(&self) -> bool {
+		match *self {
+			Outcome::Exited(status) => status.success(),
+			_ => false,
+		}
+	}
+	
+	
+	fn describe(&self) -> String {
+		match *self {
+			Outcome::Exited(status) => match status.code() {
+				Some(0) => "ok".to_owned(),
+				Some(code) => format!("exit code {}", code),
+				None => format!("stopped, {}", status),
+			},
+			Outcome::Failed(ref e) => format!("could not start: {}", e),
+			Outcome::Missing => "folder missing".to_owned(),
+		}
+	}
+}
+
+
+/// Runs a shell command in the folder of every project matching all filters, running it in at
+/// most `parallel` projects at the same time. Every line of output is prefixed with the name of
+/// the project it came from, and the exit codes are summarized at the end
+pub fn run(workspace: &Workspace, filters: &[ProjectFilter], command: &str, parallel: usize) -> Result<()> {
+	let project_list = workspace.get_project_list()?;
+	
+	let mut names: Vec<String> = project_list.get_project_names().into_iter()
+		.filter(|name| project_list.find(name).is_ok_and(|project| filters.iter().all(|filter| filter.matches(name, project))))
+		.collect();
+	names.sort_by_key(|name| name.to_lowercase());
+	
+	if names.is_empty() {
+		println!("No projects match");
+		return Ok(());
+	}
+	
+	let width = names.iter().map(|name| name.chars().count()).max().unwrap_or(0);
+	let next = AtomicUsize::new(0);
+	let outcomes = Mutex::new(Vec::new());
+	
+	thread::scope(|scope| {
+		for _ in 0..cmp::min(cmp::max(parallel, 1), names.len()) {
+			scope.spawn(|| loop {
+				let index = next.fetch_add(1, Ordering::SeqCst);
+				if index >= names.len() {
+					break;
+				}
+				
+				let outcome = run_in_project(&names[index], workspace.project_folder(&names[index]), command, width);
+				outcomes.lock().unwrap().push((index, outcome));
+			});
+		}
+	});
+	
+	let mut outcomes = outcomes.into_inner().unwrap();
+	outcomes.sort_by_key(|&(index, _)| index);
+	
+	println!();
+	println!("Summary");
+	for (index, outcome) in outcomes.iter() {
+		println!("    {:width$}  {}", names[*index], outcome.describe(), width = width);
+	}
+	
+	let failed = outcomes.iter().filter(|(_, outcome)| !outcome.is_success()).count();
+	if failed > 0 {
+		return Err(Error::Command {
+			command: command.to_owned(),
+			message: format!("Failed in {} of {} projects", failed, outcomes.len())
+		});
+	}
+	
+	Ok(())
+}
+
+
+/// Runs the command in one project's folder, forwarding its output line by line
+fn run_in_project(name: &str, path: PathBuf, command: &str, width: usize) -> Outcome {
+	if !path.is_dir() {
+		return Outcome::Missing;
+	}
+	
+	let mut child = match shell(command).current_dir(&path).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+		Ok(child) => child,
+		Err(e) => return Outcome::Failed(e),
+	};
+	
+	let prefix = format!("{:width$} | ", name, width = width);
+	let stdout = child.stdout.take().unwrap();
+	let stderr = child.stderr.take().unwrap();
+	
+	thread::scope(|scope| {
+		scope.spawn(|| forward(stderr, &prefix, &mut io::stderr()));
+		forward(stdout, &prefix, &mut io::stdout());
+	});
+	
+	match child.wait() {
+		Ok(status) => Outcome::Exited(status),
+		Err(e) => Outcome::Failed(e),
+	}
+}
+
+
+/// Copies every line from a reader to an output, prefixed. Each line is written at once so lines
+/// from projects running in parallel do not get mixed up
+fn forward<R: Read, W: Write>(reader: R, prefix: &str, output: &mut W) {
+	for line in BufReader::new(reader).split(b'\n').map_while(|line| line.ok()) {
+		let line = String::from_utf8_lossy(&line);
+		let _ = output.write_all(format!("{}{}\n", prefix, line.trim_end_matches('\r')).as_bytes());
+	}
+}
+
+
+/// Joins the arguments after '--' into a command line. A single argument is used as a command line
+/// as is, so it can contain operators such as '&&'. Several arguments are quoted, so they reach
+/// the program unchanged
+pub fn command_line(arguments: &[&str]) -> String {
+	if arguments.len() == 1 {
+		return arguments[0].to_owned();
+	}
+	
+	let quoted: Vec<String> = arguments.iter().map(|argument| quote(argument)).collect();
+	quoted.join(" ")
+}
+
+
+/// Quotes an argument for the platform's shell, if necessary
+fn quote(argument: &str) -> String {
+	let plain = !argument.is_empty() && argument.chars().all(|c| c.is_alphanumeric() || "-_./:=@%+,".contains(c));
+	
+	if plain {
+		argument.to_owned()
+	} else if cfg!(target_os = "windows") {
+		format!("\"{}\"", argument.replace('"', "\"\""))
+	} else {
+		format!("'{}'", argument.replace('\'', "'\\''"))
+	}
+}
+
+
+/// Returns a command running a command line in the platform's shell
+fn shell(command: &str) -> Command {
+	if cfg!(target_os = "windows") {
+		let mut shell = Command::new("cmd");
+		shell.arg("/C").arg(command);
+		shell
+	} else {
+		let mut shell = Command::new("sh");
+		shell.arg("-c").arg(command);
+		shell
+	}
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use error::{Error, Result};
+
+
+/// Recursively copies a folder without modifying its contents. Symbolic links are copied as links,
+/// not the files or folders they point to
+pub fn copy_folder(source: &Path, destination: &Path) -> Result<()> {
+	fs::create_dir_all(destination).map_err(|e| Error::io(destination, e))?;
+	
+	let entries = fs::read_dir(source).map_err(|e| Error::io(source, e))?;
+	
+	for entry in entries.filter_map(|entry| entry.ok()) {
+		let path = entry.path();
+		let target = destination.join(entry.file_name());
+		let file_type = entry.file_type().map_err(|e| Error::io(&path, e))?;
+		
+		if file_type.is_symlink() {
+			copy_link(&path, &target).map_err(|e| Error::io(&path, e))?;
+		} else if file_type.is_dir() {
+			copy_folder(&path, &target)?;
+		} else {
+			fs::copy(&path, &target).map_err(|e| Error::io(&path, e))?;
+		}
+	}
+	
+	Ok(())
+}
+
+
+/// Returns the total size of the files in a folder in bytes, without following symbolic links
+pub fn folder_size(path: &Path) -> Result<u64> {
+	let metadata = fs::symlink_metadata(path).map_err(|e| Error::io(path, e))?;
+	if !metadata.is_dir() {
+		return Ok(metadata.len());
+	}
+	
+	let mut size = 0;
+	for entry in fs::read_dir(path).map_err(|e| Error::io(path, e))?.filter_map(|entry| entry.ok()) {
+		size += folder_size(&entry.path())?;
+	}
+	
+	Ok(size)
+}
+
+
+/// Moves a folder, copying it if it is on another file system. Once copied, the source is renamed
+/// before it is deleted, so it is either still complete or gone. Whatever can not be deleted is left
+/// behind in a hidden folder next to it
+pub fn move_folder(source: &Path, destination: &Path) -> Result<()> {
+	match fs::rename(source, destination) {
+		Ok(()) => return Ok(()),
+		Err(ref e) if e.kind() == io::ErrorKind::CrossesDevices => (),
+		Err(e) => return Err(Error::io(source, e)),
+	}
+	
+	if let Err(e) = copy_folder(source, destination) {
+		let _ = fs::remove_dir_all(destination);
+		return Err(e);
+	}
+	
+	let name = source.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+	let moved = source.with_file_name(format!(".{}.moved-{}", name, process::id()));
+	if let Err(e) = fs::rename(source, &moved) {
+		let _ = fs::remove_dir_all(destination);
+		return Err(Error::io(source, e));
+	}
+	
+	let _ = fs::remove_dir_all(&moved);
+	Ok(())
+}
+
+
+/// Creates a symbolic link to a folder
+pub fn link_folder(target: &Path, link: &Path) -> Result<()> {
+	symlink_folder(target, link).map_err(|e| Error::io(link, e))
+}
+
+
+#[cfg(unix)]
+fn copy_link(source: &Path, link: &Path) -> io::Result<()> {
+	::std::os::unix::fs::symlink(fs::read_link(source)?, link)
+}
+
+
+#[cfg(windows)]
+fn copy_link(source: &Path, link: &Path) -> io::Result<()> {
+	let target = fs::read_link(source)?;
+	if source.is_dir() {
+		::std::os::windows::fs::symlink_dir(target, link)
+	} else {
+		::std::os::windows::fs::symlink_file(target, link)
+	}
+}
+
+
+#[cfg(unix)]
+fn symlink_folder(target: &Path, link: &Path) -> io::Result<()> {
+	::std::os::unix::fs::symlink(target, link)
+}
+
+
+#[cfg(windows)]
+fn symlink_folder(target: &Path, link: &Path) -> io::Result<()> {
+	::std::os::windows::fs::symlink_dir(target, link)
+}
@@ -0,0 +1,157 @@
+use std::cmp;
+
+
+/// The largest number of suggestions returned by `rank`
+const MAX_SUGGESTIONS: usize = 5;
+
+
+/// Returns the number of single character insertions, deletions, substitutions and swaps of
+/// adjacent characters needed to turn one text into another
+pub fn edit_distance(a: &str, b: &str) -> usize {
+	let a: Vec<char> = a.chars().collect();
+	let b: Vec<char> = b.chars().collect();
+	
+	// distances[i][j] is the distance between the first i characters of a and the first j of b
+	let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+	for (i, row) in distances.iter_mut().enumerate() {
+		row[0] = i;
+	}
+	for (j, distance) in distances[0].iter_mut().enumerate() {
+		*distance = j;
+	}
+	
+	for i in 1..a.len() + 1 {
+		for j in 1..b.len() + 1 {
+			let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+			let mut distance = cmp::min(distances[i - 1][j - 1] + cost, cmp::min(distances[i - 1][j], distances[i][j - 1]) + 1);
+			
+			if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+				distance = cmp::min(distance, distances[i - 2][j - 2] + 1);
+			}
+			
+			distances[i][j] = distance;
+		}
+	}
+	
+	distances[a.len()][b.len()]
+}
+
+
+/// Returns the number of skipped characters between the first and last character of a pattern
+/// that appears in order within a text, 'sp' in 'synth-pad' gives 5. Returns None if the pattern
+/// does not appear in the text
+pub fn subsequence_gaps(pattern: &str, text: &str) -> Option<usize> {
+	let mut pattern_chars = pattern.chars().peekable();
+	let mut first = None;
+	let mut last = 0;
+	
+	for (index, c) in text.chars().enumerate() {
+		match pattern_chars.peek() {
+			Some(&p) if p == c => {
+				first = first.or(Some(index));
+				last = index;
+				pattern_chars.next();
+			},
+			Some(_) => (),
+			None => break,
+		}
+	}
+	
+	if pattern_chars.peek().is_some() {
+		return None;
+	}
+	
+	let length = pattern.chars().count();
+	Some(first.map_or(0, |first| last + 1 - first - length))
+}
+
+
+/// Returns the names resembling a text, the closest first. Names are compared case insensitively
+/// both as a whole and by their last colon separated parts, so 'snyth' resembles 'music:synth-pad'
+pub fn rank(text: &str, names: &[String]) -> Vec<String> {
+	let text = text.to_lowercase();
+	let parts = text.split(':').count();
+	let max_distance = cmp::max(1, text.chars().count() / 3);
+	
+	let mut ranked: Vec<((usize, usize, usize), &String)> = names.iter().filter_map(|name| {
+		let lowercase = name.to_lowercase();
+		let trailing = trailing_parts(&lowercase, parts);
+		
+		// Abbreviated names are compared to the start of the trailing parts
+		let start: String = trailing.chars().take(text.chars().count()).collect();
+		let distance = cmp::min(edit_distance(&text, &lowercase), cmp::min(edit_distance(&text, trailing), edit_distance(&text, &start)));
+		if distance <= max_distance {
+			return Some(((0, distance, name.len()), name));
+		}
+		
+		subsequence_gaps(&text, &lowercase).map(|gaps| ((1, gaps, name.len()), name))
+	}).collect();
+	
+	ranked.sort();
+	ranked.into_iter().take(MAX_SUGGESTIONS).map(|(_, name)| name.clone()).collect()
+}
+
+
+/// Returns the last parts of a colon separated name: the last 2 parts of 'a:b:c' are 'b:c'
+pub fn trailing_parts(name: &str, count: usize) -> &str {
+	match name.rmatch_indices(':').nth(count.saturating_sub(1)) {
+		Some((index, _)) if count > 0 => &name[index + 1..],
+		_ => name,
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	fn names(names: &[&str]) -> Vec<String> {
+		names.iter().map(|name| name.to_string()).collect()
+	}
+	
+	
+	#[test]
+	fn edit_distance_counts_edits() {
+		assert_eq!(edit_distance("synth", "synth"), 0);
+		assert_eq!(edit_distance("", "abc"), 3);
+		assert_eq!(edit_distance("synth", "synt"), 1);
+		assert_eq!(edit_distance("synth", "sinth"), 1);
+		assert_eq!(edit_distance("kitten", "sitting"), 3);
+	}
+	
+	
+	#[test]
+	fn edit_distance_counts_swaps_as_one_edit() {
+		assert_eq!(edit_distance("snyth", "synth"), 1);
+		assert_eq!(edit_distance("ab", "ba"), 1);
+	}
+	
+	
+	#[test]
+	fn subsequence_gaps_counts_skipped_characters() {
+		assert_eq!(subsequence_gaps("sp", "synth-pad"), Some(5));
+		assert_eq!(subsequence_gaps("syn", "synth"), Some(0));
+		assert_eq!(subsequence_gaps("", "synth"), Some(0));
+		assert_eq!(subsequence_gaps("ps", "synth-pad"), None);
+	}
+	
+	
+	#[test]
+	fn trailing_parts_returns_last_parts() {
+		assert_eq!(trailing_parts("a:b:c", 1), "c");
+		assert_eq!(trailing_parts("a:b:c", 2), "b:c");
+		assert_eq!(trailing_parts("a:b:c", 5), "a:b:c");
+		assert_eq!(trailing_parts("a:b:c", 0), "a:b:c");
+	}
+	
+	
+	#[test]
+	fn rank_orders_closest_first() {
+		let projects = names(&["music:synth-pad", "music:drums", "code:rusty"]);
+		
+		assert_eq!(rank("snyth", &projects), names(&["music:synth-pad"]));
+		assert_eq!(rank("DRUMS", &projects), names(&["music:drums"]));
+		assert!(rank("xyz", &projects).is_empty());
+	}
+}
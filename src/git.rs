@@ -0,0 +1,213 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use error::{Error, Result};
+
+
+/// The .gitignore written into repositories created by rpm
+pub const DEFAULT_GITIGNORE: &str = "\
+# Build output
+/target/
+/build/
+/dist/
+
+# Editor and operating system files
+.idea/
+.vscode/
+*.swp
+*~
+.DS_Store
+Thumbs.db
+";
+
+
+/// The state of a repository's working tree and of its current branch compared to its upstream
+pub struct Status {
+	branch: Option<String>,
+	upstream: Option<(usize, usize)>,
+	changed: usize,
+	untracked: usize
+}
+
+
+impl Status {
+	/// Returns the checked out branch, None if no branch is checked out
+	pub fn branch(&self) -> Option<&str> {
+		self.branch.as_deref()
+	}
+	
+	
+	/// Returns the number of commits that are not in the upstream branch, None without an upstream
+	pub fn ahead(&self) -> Option<usize> {
+		self.upstream.map(|(ahead, _)| ahead)
+	}
+	
+	
+	/// Returns the number of upstream commits that are not in the branch, None without an upstream
+	pub fn behind(&self) -> Option<usize> {
+		self.upstream.map(|(_, behind)| behind)
+	}
+	
+	
+	/// Returns the number of tracked files with uncommitted changes
+	pub fn changed(&self) -> usize {
+		self.changed
+	}
+	
+	
+	/// Returns the number of files and folders git does not track or ignore
+	pub fn untracked(&self) -> usize {
+		self.untracked
+	}
+	
+	
+	/// Returns true if nothing is uncommitted
+	pub fn is_clean(&self) -> bool {
+		self.changed == 0 && self.untracked == 0
+	}
+}
+
+
+/// Creates a repository in a folder and adds a default .gitignore, unless the folder already
+/// contains one
+pub fn init(path: &Path) -> Result<()> {
+	run(path, &["init", "--quiet"])?;
+	
+	let gitignore = path.join(".gitignore");
+	if !gitignore.exists() {
+		fs::write(&gitignore, DEFAULT_GITIGNORE).map_err(|e| Error::io(&gitignore, e))?;
+	}
+	
+	Ok(())
+}
+
+
+/// Clones a repository into a folder, which must not exist yet. The parent folder must exist
+pub fn clone(url: &str, path: &Path) -> Result<()> {
+	let (parent, folder) = match (path.parent(), path.file_name()) {
+		(Some(parent), Some(folder)) => (parent, folder.to_string_lossy()),
+		_ => return Err(Error::Invalid(format!("Can not clone into '{}'", path.display()))),
+	};
+	
+	run(parent, &["clone", "--quiet", "--", url, &folder]).map(|_| ())
+}
+
+
+/// Returns a url with a path to a local repository made absolute, as git does not run in the
+/// current directory when cloning. Other urls are returned unchanged
+pub fn absolute_url(url: &str) -> String {
+	match Path::new(url).canonicalize() {
+		Ok(path) => path.to_string_lossy().into_owned(),
+		Err(_) => url.to_owned(),
+	}
+}
+
+
+/// Returns the status of the repository in a folder, None if the folder is not the root of a
+/// repository. Folders within a parent's repository are not repositories themselves
+pub fn status(path: &Path) -> Result<Option<Status>> {
+	if !path.join(".git").exists() {
+		return Ok(None);
+	}
+	
+	let output = run(path, &["status", "--porcelain=v2", "--branch"])?;
+	
+	let mut status = Status {
+		branch: None,
+		upstream: None,
+		changed: 0,
+		untracked: 0
+	};
+	
+	for line in output.lines() {
+		if let Some(branch) = line.strip_prefix("# branch.head ") {
+			if branch != "(detached)" {
+				status.branch = Some(branch.to_owned());
+			}
+		} else if let Some(counts) = line.strip_prefix("# branch.ab ") {
+			let counts: Vec<usize> = counts.split(' ')
+				.filter_map(|count| count.trim_start_matches(['+', '-']).parse().ok())
+				.collect();
+			if counts.len() == 2 {
+				status.upstream = Some((counts[0], counts[1]));
+			}
+		} else if line.starts_with("? ") {
+			status.untracked += 1;
+		} else if !line.starts_with('#') && !line.starts_with("! ") {
+			status.changed += 1;
+		}
+	}
+	
+	Ok(Some(status))
+}
+
+
+/// Returns the name of the folder git would clone a repository into, 'synth' for both
+/// 'https://example.com/music/synth.git' and 'git@example.com:music/synth'
+pub fn repository_name(url: &str) -> Option<&str> {
+	let url = url.trim_end_matches(['/', '\\']);
+	let name = url.rsplit(['/', '\\', ':']).next().unwrap_or(url);
+	let name = name.strip_suffix(".git").unwrap_or(name);
+	
+	if name.is_empty() { None } else { Some(name) }
+}
+
+
+/// Runs git in a folder, returning what it printed
+fn run(directory: &Path, arguments: &[&str]) -> Result<String> {
+	let command = format!("git {}", arguments.join(" "));
+	
+	let output = Command::new("git").args(arguments).current_dir(directory).output().map_err(|e| Error::Command {
+		command: command.clone(),
+		message: format!("Failed to launch git: {}", e)
+	})?;
+	
+	if !output.status.success() {
+		let stderr = String::from_utf8_lossy(&output.stderr);
+		return Err(Error::Command {
+			message: match stderr.trim() {
+				"" => format!("Exited with {}", output.status),
+				stderr => stderr.to_owned(),
+			},
+			command
+		});
+	}
+	
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::env;
+	use std::process;
+	
+	
+	#[test]
+	fn repository_name_strips_paths_and_extension() {
+		assert_eq!(repository_name("https://example.com/music/synth.git"), Some("synth"));
+		assert_eq!(repository_name("git@example.com:music/synth"), Some("synth"));
+		assert_eq!(repository_name("/srv/git/synth.git/"), Some("synth"));
+		assert_eq!(repository_name(".git"), None);
+	}
+	
+	
+	#[test]
+	fn clone_does_not_read_urls_as_options() {
+		let path = env::temp_dir().join(format!("rpm-test-git-options-{}", process::id()));
+		let _ = fs::remove_dir_all(&path);
+		fs::create_dir_all(&path).unwrap();
+		
+		let marker = path.join("marker");
+		let url = format!("--upload-pack=touch {}", marker.display());
+		match clone(&url, &path.join("synth")) {
+			Err(Error::Command { message, .. }) => assert!(message.contains(&url), "{}", message),
+			_ => panic!("'{}' should not be cloned", url),
+		}
+		assert!(!marker.exists());
+		
+		fs::remove_dir_all(&path).unwrap();
+	}
+}
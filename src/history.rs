@@ -0,0 +1,198 @@
+use std::path::PathBuf;
+
+use config::data_directory;
+use database;
+use error::Result;
+use time;
+
+
+/// A workspace that was switched to
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorkspaceVisit {
+	/// The name of the workspace
+	pub name: String,
+	
+	/// The time of the switch, in seconds since the unix epoch
+	pub time: u64
+}
+
+
+/// A project that was opened
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectVisit {
+	/// The name of the workspace containing the project
+	pub workspace: String,
+	
+	/// The name of the project, such as 'project1:project11'
+	pub name: String,
+	
+	/// The time the project was opened, in seconds since the unix epoch
+	pub time: u64
+}
+
+
+/// The most recently used workspaces and projects, newest first
+#[derive(Default, Serialize, Deserialize)]
+pub struct History {
+	#[serde(default)]
+	workspaces: Vec<WorkspaceVisit>,
+	
+	#[serde(default)]
+	projects: Vec<ProjectVisit>
+}
+
+const HISTORY_FILE_NAME: &str = "history.json";
+
+/// The maximum number of entries kept for both workspaces and projects
+const MAX_HISTORY_LENGTH: usize = 50;
+
+
+impl History {
+	/// Load the history from a file, an empty history is used if there is no file
+	pub fn get() -> Result<History> {
+		let history_file_path = History::path()?;
+		
+		if !database::exists(&history_file_path) {
+			return Ok(History::default());
+		}
+		
+		database::load(&history_file_path)
+	}
+	
+	
+	/// Write the history to a file
+	pub fn save(&self) -> Result<()> {
+		database::save(&History::path()?, self)
+	}
+	
+	
+	/// Loads the history, applies a modification and saves it again. Other processes can not modify
+	/// the history in the meantime
+	pub fn update<T, F>(modify: F) -> Result<T>
+		where F: FnOnce(&mut History) -> Result<T> {
+		let _lock = database::lock(&History::path()?)?;
+		
+		let mut history = History::get()?;
+		let result = modify(&mut history)?;
+		history.save()?;
+		
+		Ok(result)
+	}
+	
+	
+	/// Records that a workspace was switched to
+	pub fn add_workspace(&mut self, name: &str) {
+		let lowercase = name.to_lowercase();
+		self.workspaces.retain(|visit| visit.name.to_lowercase() != lowercase);
+		
+		self.workspaces.insert(0, WorkspaceVisit {
+			name: name.to_owned(),
+			time: time::now()
+		});
+		self.workspaces.truncate(MAX_HISTORY_LENGTH);
+	}
+	
+	
+	/// Records that a project in a workspace was opened
+	pub fn add_project(&mut self, workspace: &str, name: &str) {
+		let workspace_lowercase = workspace.to_lowercase();
+		let lowercase = name.to_lowercase();
+		self.projects.retain(|visit| visit.workspace.to_lowercase() != workspace_lowercase || visit.name.to_lowercase() != lowercase);
+		
+		self.projects.insert(0, ProjectVisit {
+			workspace: workspace.to_owned(),
+			name: name.to_owned(),
+			time: time::now()
+		});
+		self.projects.truncate(MAX_HISTORY_LENGTH);
+	}
+	
+	
+	/// Renames a workspace in the recorded visits, including the visits of its projects
+	pub fn rename_workspace(&mut self, from: &str, to: &str) {
+		let lowercase = from.to_lowercase();
+		
+		for visit in self.workspaces.iter_mut().filter(|visit| visit.name.to_lowercase() == lowercase) {
+			visit.name = to.to_owned();
+		}
+		for visit in self.projects.iter_mut().filter(|visit| visit.workspace.to_lowercase() == lowercase) {
+			visit.workspace = to.to_owned();
+		}
+	}
+	
+	
+	/// Renames a project and its subprojects in the recorded visits, 'music:synth' becomes
+	/// 'archive:synth' when 'music' is renamed to 'archive'
+	pub fn rename_project(&mut self, workspace: &str, from: &str, to: &str) {
+		let workspace = workspace.to_lowercase();
+		let lowercase = from.to_lowercase();
+		let depth = from.split(':').count();
+		
+		for visit in self.projects.iter_mut().filter(|visit| visit.workspace.to_lowercase() == workspace) {
+			let name = visit.name.to_lowercase();
+			if name == lowercase {
+				visit.name = to.to_owned();
+			} else if name.starts_with(&(lowercase.clone() + ":")) {
+				let rest: Vec<&str> = visit.name.split(':').skip(depth).collect();
+				visit.name = format!("{}:{}", to, rest.join(":"));
+			}
+		}
+	}
+	
+	
+	/// Returns the recently used workspaces, newest first
+	pub fn workspaces(&self) -> &[WorkspaceVisit] {
+		&self.workspaces
+	}
+	
+	
+	/// Returns the recently opened projects, newest first
+	pub fn projects(&self) -> &[ProjectVisit] {
+		&self.projects
+	}
+	
+	
+	/// Return the path to the history file
+	fn path() -> Result<PathBuf> {
+		let mut path = data_directory()?;
+		path.push(HISTORY_FILE_NAME);
+		Ok(path)
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	fn names(history: &History) -> Vec<String> {
+		history.projects().iter().map(|visit| format!("{}/{}", visit.workspace, visit.name)).collect()
+	}
+	
+	
+	#[test]
+	fn rename_project_renames_subprojects() {
+		let mut history = History::default();
+		history.add_project("ws", "music:synth:pad");
+		history.add_project("ws", "music");
+		history.add_project("ws", "musical");
+		history.add_project("other", "music");
+		
+		history.rename_project("ws", "MUSIC", "archive:sound");
+		assert_eq!(names(&history), vec!["other/music", "ws/musical", "ws/archive:sound", "ws/archive:sound:synth:pad"]);
+	}
+	
+	
+	#[test]
+	fn rename_workspace_renames_visits_of_projects() {
+		let mut history = History::default();
+		history.add_workspace("ws");
+		history.add_project("ws", "music");
+		history.add_project("other", "music");
+		
+		history.rename_workspace("WS", "studio");
+		assert_eq!(history.workspaces()[0].name, "studio");
+		assert_eq!(names(&history), vec!["other/music", "studio/music"]);
+	}
+}
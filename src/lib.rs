@@ -0,0 +1,80 @@
+//! Manages workspaces and the tree of projects within them.
+//!
+//! The global list of workspaces is loaded with [`WorkspaceList::get`](workspace/struct.WorkspaceList.html#method.get),
+//! each [`Workspace`](workspace/struct.Workspace.html) then gives access to its
+//! [`ProjectList`](project/struct.ProjectList.html). Projects are named by their path in the
+//! project tree, separated by colons: 'music:synth'.
+//!
+//! ```no_run
+//! extern crate rpm;
+//!
+//! use rpm::{Project, WorkspaceList};
+//!
+//! # fn main() { run().unwrap() }
+//! # fn run() -> rpm::Result<()> {
+//! let mut workspace = WorkspaceList::get()?.current()?;
+//! workspace.add_project(Project::from_str("music:synth"), None)?;
+//!
+//! println!("{}", workspace.get_project_path("music:synth")?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! No function in this crate prints to the terminal or exits the process, all failures are
+//! reported as an [`Error`](error/enum.Error.html).
+
+#![warn(missing_docs)]
+
+extern crate serde;
+#[cfg(unix)]
+extern crate libc;
+extern crate serde_json;
+
+#[macro_use]
+extern crate serde_derive;
+
+/// The error type shared by all operations
+pub mod error;
+
+/// Workspaces and the global list of workspaces
+pub mod workspace;
+
+/// Projects and the project database of a workspace
+pub mod project;
+
+/// Fuzzy matching of names, used to suggest and resolve project names
+pub mod fuzzy;
+
+/// Removed projects that can be restored
+pub mod trash;
+
+/// Templates that new projects are created from, and the global template store
+pub mod template;
+
+/// The location of the global files shared by all workspaces
+pub mod config;
+
+/// Global preferences
+pub mod settings;
+
+/// The recently used workspaces and projects
+pub mod history;
+
+/// Launching editors and file browsers for projects
+pub mod opener;
+
+/// Creating and cloning git repositories for projects
+pub mod git;
+
+/// Timestamps and date formatting
+pub mod time;
+
+/// Finding and repairing inconsistencies between the databases and the file system
+pub mod doctor;
+
+mod database;
+mod files;
+
+pub use error::{Error, Result};
+pub use workspace::{Workspace, WorkspaceList};
+pub use project::{Project, ProjectList};
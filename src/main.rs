@@ -1,4 +1,3 @@
-
 extern crate getch;
 extern crate clap;
 
@@ -8,18 +7,195 @@ extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
 
+extern crate rpm;
+
 use clap::ArgMatches;
 
 mod args;
+mod exec;
+mod shell;
+mod tui;
+
+use rpm::Error;
+use rpm::workspace::{Workspace, WorkspaceList};
+use rpm::project::Project;
+use rpm::template::TemplateList;
+use rpm::time;
+use rpm::opener::Opener;
+use rpm::settings::Settings;
+use rpm::history::History;
+use rpm::doctor;
+use rpm::git;
+
+
+use std::path::{Path, PathBuf};
+
 
-mod workspace;
-use workspace::{Workspace, WorkspaceList};
+/// How lists and names are printed
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+	/// Sentences and the project tree, for humans
+	Tree,
+	/// One name or path per line
+	Plain,
+	/// JSON objects with all metadata
+	Json
+}
+
+
+/// A workspace as printed with '--format json'
+#[derive(Serialize)]
+struct WorkspaceSummary<'a> {
+	name: &'a str,
+	path: &'a str,
+	projects: Option<usize>,
+	exists: bool,
+	active: bool
+}
 
-mod project;
-use project::Project;
 
+/// A project as printed with '--format json'
+#[derive(Serialize)]
+struct ProjectSummary<'a> {
+	name: String,
+	path: String,
+	exists: bool,
+	#[serde(rename = "type")]
+	project_type: Option<&'a str>,
+	description: Option<&'a str>,
+	tags: &'a [String],
+	priority: Option<i32>,
+	created: Option<u64>,
+	last_opened: Option<u64>,
+	editor: Option<&'a str>,
+	remote: Option<&'a str>
+}
 
-use std::path::PathBuf;
+
+impl<'a> WorkspaceSummary<'a> {
+	fn new(workspace: &'a Workspace, workspace_list: &WorkspaceList) -> Self {
+		WorkspaceSummary {
+			name: workspace.name(),
+			path: workspace.path(),
+			projects: workspace.get_project_list().ok().map(|list| list.get_project_names().len()),
+			exists: PathBuf::from(workspace.path()).is_dir(),
+			active: workspace_list.is_current(workspace)
+		}
+	}
+}
+
+
+impl<'a> ProjectSummary<'a> {
+	fn new(workspace: &Workspace, name: &str, project: &'a Project) -> Self {
+		let path = workspace.project_folder(name);
+		
+		ProjectSummary {
+			name: name.to_owned(),
+			exists: path.is_dir(),
+			path: path.to_string_lossy().into_owned(),
+			project_type: project.project_type(),
+			description: project.description(),
+			tags: project.tags(),
+			priority: project.priority(),
+			created: project.created(),
+			last_opened: project.last_opened(),
+			editor: project.editor(),
+			remote: project.remote()
+		}
+	}
+}
+
+
+/// The git status of a project as printed with '--format json'
+#[derive(Serialize)]
+struct StatusSummary {
+	name: String,
+	path: String,
+	exists: bool,
+	repository: bool,
+	branch: Option<String>,
+	changed: usize,
+	untracked: usize,
+	ahead: Option<usize>,
+	behind: Option<usize>,
+	error: Option<String>
+}
+
+
+/// What 'rpm status' found in the folder of a project
+enum FolderStatus {
+	Missing,
+	NoRepository,
+	Repository(git::Status),
+	Failed(Error)
+}
+
+
+impl FolderStatus {
+	fn get(workspace: &Workspace, name: &str) -> Self {
+		let path = match workspace.get_project_path(name) {
+			Ok(path) => path,
+			Err(_) => return FolderStatus::Missing,
+		};
+		
+		match git::status(Path::new(&path)) {
+			Ok(Some(status)) => FolderStatus::Repository(status),
+			Ok(None) => FolderStatus::NoRepository,
+			Err(e) => FolderStatus::Failed(e),
+		}
+	}
+	
+	
+	/// Returns true if the folder contains work that only exists on this computer
+	fn has_local_work(&self) -> bool {
+		match *self {
+			FolderStatus::Repository(ref status) => !status.is_clean() || status.ahead().is_some_and(|ahead| ahead > 0),
+			_ => false,
+		}
+	}
+}
+
+
+impl StatusSummary {
+	fn new(workspace: &Workspace, name: &str, status: &FolderStatus) -> Self {
+		let mut summary = StatusSummary {
+			name: name.to_owned(),
+			path: workspace.project_folder(name).to_string_lossy().into_owned(),
+			exists: true,
+			repository: false,
+			branch: None,
+			changed: 0,
+			untracked: 0,
+			ahead: None,
+			behind: None,
+			error: None
+		};
+		
+		match *status {
+			FolderStatus::Missing => summary.exists = false,
+			FolderStatus::NoRepository => (),
+			FolderStatus::Repository(ref status) => {
+				summary.repository = true;
+				summary.branch = status.branch().map(|branch| branch.to_owned());
+				summary.changed = status.changed();
+				summary.untracked = status.untracked();
+				summary.ahead = status.ahead();
+				summary.behind = status.behind();
+			},
+			FolderStatus::Failed(ref e) => {
+				summary.repository = true;
+				summary.error = Some(e.to_string());
+			},
+		}
+		
+		summary
+	}
+}
+
+
+fn print_json<T: serde::Serialize>(value: &T) {
+	println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
 
 
 macro_rules! try_fatal {
@@ -32,6 +208,20 @@ macro_rules! try_fatal {
 fn main() {
 	let matches = args::get_matches();
 	
+	// Global arguments may be given before or after the subcommand
+	let config = matches.value_of("config").or_else(|| {
+		matches.subcommand().1.and_then(|m| m.value_of("config"))
+	});
+	if let Some(directory) = config {
+		rpm::config::set_data_directory(directory);
+	}
+	
+	let format = match matches.value_of("format").or_else(|| matches.subcommand().1.and_then(|m| m.value_of("format"))) {
+		Some("json") => Format::Json,
+		Some("plain") => Format::Plain,
+		_ => Format::Tree,
+	};
+	
 	match matches.subcommand() {
 		// Create a new workspace
 		("new", Some(m)) => new_item(m),
@@ -39,35 +229,77 @@ fn main() {
 		// Remove project or workspace
 		("remove", Some(m)) => remove_item(m),
 		
+		// Edit the metadata of a project
+		("edit", Some(m)) => edit_item(m),
+		
+		// Rename or move a project
+		("mv", Some(m)) => move_project(m),
+		
 		// Switch to a new workspace
 		("switch", Some(m)) => switch_workspace(m),
 		
 		// Open a project
 		("open", Some(m)) => open_project(m),
 		
+		// Display the path to a project
+		("path", Some(m)) => display_project_path(m, format),
+		("shell-init", Some(m)) => print!("{}", shell::init_script(m.value_of("shell").unwrap())),
+		
+		// Complete commands and names in the shell
+		("completions", Some(m)) => shell::print_completions(m.value_of("shell").unwrap()),
+		("complete-names", Some(m)) => shell::print_names(m.value_of("type").unwrap()),
+		
+		// Configure the editor used to open projects
+		("editor", Some(m)) => configure_editor(m),
+		
+		// Rename, move or clone a workspace
+		("workspace", Some(m)) => manage_workspace(m),
+		
 		// Display the current workspace
-		("current", Some(m)) => display_current_workspace(m),
+		("current", Some(m)) => display_current_workspace(m, format),
+		
+		// List all workspaces or projects
+		("list", Some(m)) => list_items(m, format),
+		
+		// Display the git status of all projects
+		("status", Some(_)) => display_status(format),
+		
+		// Run a command in every project
+		("exec", Some(m)) => exec_command(m),
+		
+		// Display recently used workspaces and projects
+		("recent", Some(m)) => display_recent(m),
+		
+		// Add existing directories as projects
+		("adopt", Some(m)) => adopt_project(m),
+		("clone", Some(m)) => clone_project(m),
+		("scan", Some(m)) => scan_projects(m),
+		
+		// Browse workspaces and projects interactively
+		("tui", Some(_)) => try_fatal!(tui::run()),
+		
+		// Check for and repair inconsistencies
+		("doctor", Some(m)) => run_doctor(m),
+		
+		// Manage removed projects
+		("trash", Some(m)) => manage_trash(m),
+		
+		// Manage the global templates
+		("template", Some(m)) => manage_templates(m),
 		
 		_ => ()
 	}
 }
 
-fn pause() {
-	let getch = getch::Getch::new();
-	
-	println!("Press any key to continue...");
-	getch.getch().unwrap();
-}
-
-
 fn fail_with_message(msg: &str) -> ! {
-	println!("{}", msg);
+	eprintln!("{}", msg);
 	std::process::exit(1);
 }
 
 
-fn fail_with_error(err: String) -> ! {
-	fail_with_message(&format!("Error: {}", err));
+fn fail_with_error(err: Error) -> ! {
+	eprintln!("Error: {}", err);
+	std::process::exit(err.exit_code());
 }
 
 
@@ -76,6 +308,36 @@ fn get_current_workspace() -> Workspace {
 }
 
 
+/// Returns the full name of the project meant by a possibly abbreviated name, letting the user
+/// choose when several projects match and stdin is a terminal
+fn resolve_project_name(workspace: &Workspace, name: &str) -> String {
+	use std::io::{stdin, stderr, IsTerminal, Write};
+	
+	let project_list = try_fatal!(workspace.get_project_list());
+	let candidates = match project_list.resolve(name) {
+		Ok(name) => return name,
+		Err(Error::Ambiguous { ref candidates, .. }) if stdin().is_terminal() => candidates.clone(),
+		Err(err) => fail_with_error(err),
+	};
+	
+	// The choices go to stderr, so the picker also works when the output is captured by 'rcd'
+	eprintln!("'{}' matches several projects:", name);
+	for (index, candidate) in candidates.iter().enumerate() {
+		eprintln!("{:>3}) {}", index + 1, candidate);
+	}
+	eprint!("Select a project (1-{}): ", candidates.len());
+	stderr().flush().unwrap();
+	
+	let mut answer = String::new();
+	stdin().read_line(&mut answer).expect("Did not enter a correct string");
+	
+	match answer.trim().parse::<usize>() {
+		Ok(index) if index >= 1 && index <= candidates.len() => candidates[index - 1].clone(),
+		_ => fail_with_message("Error: No project selected"),
+	}
+}
+
+
 fn get_confirmation(message: &str) -> bool {
 	use std::io::{stdin, stdout, Write};
 	
@@ -101,26 +363,31 @@ fn get_confirmation(message: &str) -> bool {
 }
 
 
-fn new_item(matches: &ArgMatches) {
+/// Returns the absolute path of a new workspace: a folder with its name within a directory, or
+/// within the current directory
+fn get_new_workspace_path(directory: Option<&str>, name: &str) -> String {
 	use std::env::current_dir;
 	use std::path::MAIN_SEPARATOR;
 	
+	let mut absolute_path = current_dir().unwrap();
+	absolute_path.push( PathBuf::from(
+	if let Some(directory) = directory {
+		directory.to_owned() + &MAIN_SEPARATOR.to_string() + name
+	} else {
+		name.to_owned()
+	}));
+	absolute_path.to_str().unwrap().to_owned()
+}
+
+
+fn new_item(matches: &ArgMatches) {
 	let name = matches.value_of("name").unwrap();
-	let path = {
-		let mut absolute_path = current_dir().unwrap();
-		absolute_path.push( PathBuf::from(
-		if matches.is_present("path") {
-			matches.value_of("path").unwrap().to_owned() + &MAIN_SEPARATOR.to_string() + name
-		} else {
-			name.to_owned()
-		}));
-		absolute_path.to_str().unwrap().to_owned()
-	};
+	let path = get_new_workspace_path(matches.value_of("path"), name);
 	
 	
 	match matches.value_of("type") {
 		Some("workspace") => new_workspace(name, &path),
-		Some("project") => new_project(name),
+		Some("project") => new_project(name, matches.value_of("template"), matches.is_present("git")),
 		Some(t) => fail_with_message(&format!("Error: {} is not recognized as internal type", t)),
 		None => fail_with_message("Error: Invalid argument parameters"),
 	}
@@ -133,9 +400,18 @@ fn new_workspace(name: &str, path: &str) {
 }
 
 
-fn new_project(name: &str) {
+fn new_project(name: &str, template: Option<&str>, git: bool) {
 	let mut workspace = get_current_workspace();
-	try_fatal!(workspace.add_project(Project::from_str(name)));
+	
+	let template = template.map(|template| try_fatal!(workspace.get_template(template)));
+	try_fatal!(workspace.add_project(Project::from_str(name), template.as_ref()));
+	
+	if git {
+		if let Err(e) = workspace.init_repository(name) {
+			eprintln!("Project '{}' was created without a repository", name);
+			fail_with_error(e);
+		}
+	}
 }
 
 
@@ -144,19 +420,290 @@ fn switch_workspace(matches: &ArgMatches) {
 	let workspace_list = try_fatal!(WorkspaceList::get());
 	let workspace = try_fatal!(workspace_list.lookup(name));
 	try_fatal!(workspace.set_active());
+	
+	try_fatal!(History::update(|history| {
+		history.add_workspace(workspace.name());
+		Ok(())
+	}));
+}
+
+
+fn manage_workspace(matches: &ArgMatches) {
+	match matches.subcommand() {
+		("rename", Some(m)) => rename_workspace(m.value_of("name").unwrap(), m.value_of("new name").unwrap()),
+		("move", Some(m)) => move_workspace(m.value_of("name").unwrap(), m.value_of("path").unwrap()),
+		("clone", Some(m)) => clone_workspace(m),
+		_ => fail_with_message("Error: Invalid argument parameters"),
+	}
+}
+
+
+fn rename_workspace(name: &str, new_name: &str) {
+	let mut workspace = try_fatal!(try_fatal!(WorkspaceList::get()).lookup(name));
+	try_fatal!(workspace.rename(new_name));
+	println!("Workspace '{}' renamed to '{}'!", name, new_name);
+}
+
+
+fn move_workspace(name: &str, path: &str) {
+	use std::env::current_dir;
+	
+	let path = current_dir().unwrap().join(path);
+	
+	let mut workspace = try_fatal!(try_fatal!(WorkspaceList::get()).lookup(name));
+	try_fatal!(workspace.relocate(path.to_str().unwrap()));
+	println!("Workspace '{}' moved to '{}'!", workspace.name(), workspace.path());
+}
+
+
+fn clone_workspace(matches: &ArgMatches) {
+	let new_name = matches.value_of("new name").unwrap();
+	let path = get_new_workspace_path(matches.value_of("path"), new_name);
+	
+	let workspace = try_fatal!(try_fatal!(WorkspaceList::get()).lookup(matches.value_of("name").unwrap()));
+	let clone = try_fatal!(workspace.clone_to(new_name, &path, matches.is_present("projects")));
+	println!("Workspace '{}' created in '{}'!", clone.name(), clone.path());
 }
 
 
-fn display_current_workspace(matches: &ArgMatches) {
+fn display_current_workspace(matches: &ArgMatches, format: Format) {
 	let workspace_list = try_fatal!(WorkspaceList::get());
 	let current = try_fatal!(workspace_list.current());
 	
 	if matches.is_present("list projects") {
-		let project_list = try_fatal!(current.get_project_list());
+		print_projects(&current, format);
+		return;
+	}
+	
+	match format {
+		Format::Tree => println!("Current workspace: '{}'", current.name()),
+		Format::Plain => println!("{}", current.name()),
+		Format::Json => print_json(&WorkspaceSummary::new(&current, &workspace_list)),
+	}
+}
+
+
+fn list_items(matches: &ArgMatches, format: Format) {
+	let format = if matches.is_present("json") { Format::Json } else { format };
+	
+	match matches.value_of("type") {
+		Some("workspaces") | Some("workspace") => list_workspaces(format),
+		Some("projects") | Some("project") => print_projects(&get_current_workspace(), format),
+		Some(t) => fail_with_message(&format!("Error: '{}' is not recognized as internal type", t)),
+		None => fail_with_message("Error: Invalid argument parameters"),
+	}
+}
+
+
+fn list_workspaces(format: Format) {
+	let workspace_list = try_fatal!(WorkspaceList::get());
+	
+	let summaries: Vec<WorkspaceSummary> = workspace_list.workspaces().iter()
+		.map(|workspace| WorkspaceSummary::new(workspace, &workspace_list))
+		.collect();
+	
+	match format {
+		Format::Json => return print_json(&summaries),
+		Format::Plain => {
+			for summary in summaries.iter() {
+				println!("{}", summary.name);
+			}
+			return;
+		},
+		Format::Tree => (),
+	}
+	
+	if summaries.is_empty() {
+		println!("No workspaces");
+		return;
+	}
+	
+	println!("Workspaces");
+	for summary in summaries.iter() {
+		let projects = match summary.projects {
+			Some(1) => "1 project".to_owned(),
+			Some(count) => format!("{} projects", count),
+			None => "no project database".to_owned(),
+		};
 		
-		println!("{}", project_list);
+		println!("{} {} ({}) '{}'{}",
+			if summary.active { "*" } else { " " },
+			summary.name,
+			projects,
+			summary.path,
+			if summary.exists { "" } else { " [missing]" });
+	}
+}
+
+
+fn print_projects(workspace: &Workspace, format: Format) {
+	let project_list = try_fatal!(workspace.get_project_list());
+	
+	match format {
+		Format::Tree => println!("{}", project_list),
+		Format::Plain => {
+			for line in project_list.tree() {
+				println!("{}", line.name);
+			}
+		},
+		Format::Json => {
+			let summaries: Vec<ProjectSummary> = project_list.tree().iter()
+				.map(|line| ProjectSummary::new(workspace, &line.name, line.project))
+				.collect();
+			print_json(&summaries);
+		},
+	}
+}
+
+
+fn display_project_path(matches: &ArgMatches, format: Format) {
+	let workspace = get_current_workspace();
+	let name = resolve_project_name(&workspace, matches.value_of("name").unwrap());
+	
+	if format == Format::Json {
+		let project_list = try_fatal!(workspace.get_project_list());
+		let project = try_fatal!(project_list.find(&name));
+		print_json(&ProjectSummary::new(&workspace, &name, project));
 	} else {
-		println!("Current workspace: '{}'", current.name());
+		println!("{}", try_fatal!(workspace.get_project_path(&name)));
+	}
+}
+
+
+fn display_status(format: Format) {
+	let workspace = get_current_workspace();
+	let project_list = try_fatal!(workspace.get_project_list());
+	let lines = project_list.tree();
+	
+	let statuses: Vec<FolderStatus> = lines.iter().map(|line| FolderStatus::get(&workspace, &line.name)).collect();
+	
+	match format {
+		Format::Json => {
+			let summaries: Vec<StatusSummary> = lines.iter().zip(statuses.iter())
+				.map(|(line, status)| StatusSummary::new(&workspace, &line.name, status))
+				.collect();
+			return print_json(&summaries);
+		},
+		Format::Plain => {
+			for (line, status) in lines.iter().zip(statuses.iter()) {
+				if status.has_local_work() {
+					println!("{}", line.name);
+				}
+			}
+			return;
+		},
+		Format::Tree => (),
+	}
+	
+	fn count(number: usize, text: &str) -> Option<String> {
+		if number > 0 { Some(format!("{} {}", number, text)) } else { None }
+	}
+	
+	let mut rows = vec![["Projects".to_owned(), "Branch".to_owned(), "Changes".to_owned(), "Upstream".to_owned()]];
+	for (line, status) in lines.iter().zip(statuses.iter()) {
+		let name = format!("{}{}", line.prefix, line.project.name());
+		
+		rows.push(match *status {
+			FolderStatus::Missing => [name, "folder missing".to_owned(), String::new(), String::new()],
+			FolderStatus::NoRepository => [name, "-".to_owned(), String::new(), String::new()],
+			FolderStatus::Failed(ref e) => [name, format!("error: {}", e), String::new(), String::new()],
+			FolderStatus::Repository(ref status) => {
+				let changes: Vec<String> = count(status.changed(), "changed").into_iter()
+					.chain(count(status.untracked(), "untracked"))
+					.collect();
+				
+				let upstream: Vec<String> = count(status.ahead().unwrap_or(0), "ahead").into_iter()
+					.chain(count(status.behind().unwrap_or(0), "behind"))
+					.collect();
+				
+				[
+					name,
+					status.branch().unwrap_or("(detached)").to_owned(),
+					if changes.is_empty() { "clean".to_owned() } else { changes.join(", ") },
+					match status.ahead() {
+						None => "no upstream".to_owned(),
+						Some(_) if upstream.is_empty() => "up to date".to_owned(),
+						Some(_) => upstream.join(", "),
+					}
+				]
+			},
+		});
+	}
+	
+	let mut widths = [0; 4];
+	for row in rows.iter() {
+		for (width, cell) in widths.iter_mut().zip(row.iter()) {
+			*width = std::cmp::max(*width, cell.chars().count());
+		}
+	}
+	
+	for row in rows.iter() {
+		let mut text = String::new();
+		for (width, cell) in widths.iter().zip(row.iter()) {
+			text += cell;
+			text += &" ".repeat(width - cell.chars().count() + 2);
+		}
+		println!("{}", text.trim_end());
+	}
+	
+	let repositories = statuses.iter().filter(|status| matches!(**status, FolderStatus::Repository(_))).count();
+	let uncommitted = statuses.iter().filter(|status| matches!(**status, FolderStatus::Repository(ref status) if !status.is_clean())).count();
+	let unpushed = statuses.iter().filter(|status| matches!(**status, FolderStatus::Repository(ref status) if status.ahead().is_some_and(|ahead| ahead > 0))).count();
+	
+	println!();
+	println!("{} {}, {} with uncommitted changes, {} with unpushed commits",
+		repositories,
+		if repositories == 1 { "repository" } else { "repositories" },
+		uncommitted,
+		unpushed);
+}
+
+
+fn exec_command(matches: &ArgMatches) {
+	use rpm::project::ProjectFilter;
+	
+	let parallel = match matches.value_of("parallel").unwrap().parse::<usize>() {
+		Ok(parallel) if parallel > 0 => parallel,
+		_ => fail_with_message(&format!("Error: '{}' is not a valid number", matches.value_of("parallel").unwrap())),
+	};
+	
+	let filters: Vec<ProjectFilter> = matches.values_of("filter").into_iter().flatten()
+		.map(|filter| try_fatal!(ProjectFilter::parse(filter)))
+		.collect();
+	
+	let command: Vec<&str> = matches.values_of("command").unwrap().collect();
+	
+	try_fatal!(exec::run(&get_current_workspace(), &filters, &exec::command_line(&command), parallel));
+}
+
+
+fn display_recent(matches: &ArgMatches) {
+	let count = match matches.value_of("count").unwrap().parse::<usize>() {
+		Ok(count) => count,
+		Err(_) => fail_with_message(&format!("Error: '{}' is not a valid number", matches.value_of("count").unwrap())),
+	};
+	
+	let show_workspaces = !matches.is_present("projects");
+	let show_projects = !matches.is_present("workspaces");
+	
+	let history = try_fatal!(History::get());
+	
+	if show_workspaces {
+		println!("Recent workspaces");
+		for visit in history.workspaces().iter().take(count) {
+			println!("    {} ({})", visit.name, time::format_elapsed(visit.time));
+		}
+	}
+	
+	if show_workspaces && show_projects {
+		println!();
+	}
+	
+	if show_projects {
+		println!("Recent projects");
+		for visit in history.projects().iter().take(count) {
+			println!("    {} in '{}' ({})", visit.name, visit.workspace, time::format_elapsed(visit.time));
+		}
 	}
 }
 
@@ -165,25 +712,154 @@ fn remove_item(matches: &ArgMatches) {
 	let name = matches.value_of("name").unwrap();
 	
 	match matches.value_of("type") {
-		Some("workspace") =>remove_workspace(name),
-		Some("project") => remove_project(name), // fail_with_message("Removal of projects has been disabled in order to prevent unwanted loss of projects/data"),
+		Some("workspace") => remove_workspace(name, matches.is_present("delete files"), matches.is_present("dry run")),
+		Some("project") => remove_project(name, matches.is_present("delete files"), matches.is_present("dry run")),
+		Some(t) => fail_with_message(&format!("Error: '{}' is not recognized as internal type", t)),
+		None => fail_with_message("Error: Invalid argument parameters"),
+	}
+}
+
+
+fn edit_item(matches: &ArgMatches) {
+	let name = matches.value_of("name").unwrap();
+	
+	match matches.value_of("type") {
+		Some("project") => edit_project(name, matches),
 		Some(t) => fail_with_message(&format!("Error: '{}' is not recognized as internal type", t)),
 		None => fail_with_message("Error: Invalid argument parameters"),
 	}
 }
 
 
-fn remove_workspace(name: &str) {
-	let mut workspace_list = try_fatal!(WorkspaceList::get());
-	try_fatal!(workspace_list.remove(name));
-	try_fatal!(workspace_list.save());
+fn edit_project(name: &str, matches: &ArgMatches) {
+	fn non_empty(text: &str) -> Option<String> {
+		if text.is_empty() { None } else { Some(text.to_owned()) }
+	}
+	
+	let priority = match matches.value_of("priority") {
+		Some(priority) => match priority.parse::<i32>() {
+			Ok(priority) => Some(priority),
+			Err(_) => fail_with_message(&format!("Error: '{}' is not a valid priority", priority)),
+		},
+		None => None,
+	};
+	
+	let mut workspace = get_current_workspace();
+	let name = &resolve_project_name(&workspace, name);
+	let project = try_fatal!(workspace.edit_project(name, |project| {
+		if let Some(description) = matches.value_of("description") {
+			project.set_description(non_empty(description));
+		}
+		if let Some(tags) = matches.values_of("tag") {
+			for tag in tags {
+				project.add_tag(tag);
+			}
+		}
+		if let Some(tags) = matches.values_of("untag") {
+			for tag in tags {
+				project.remove_tag(tag);
+			}
+		}
+		if let Some(project_type) = matches.value_of("project type") {
+			project.set_project_type(non_empty(project_type));
+		}
+		if priority.is_some() {
+			project.set_priority(priority);
+		} else if matches.is_present("clear priority") {
+			project.set_priority(None);
+		}
+		if let Some(remote) = matches.value_of("remote") {
+			project.set_remote(non_empty(remote));
+		}
+	}));
+	
+	println!("{}", project);
+}
+
+
+fn remove_workspace(name: &str, delete_files: bool, dry_run: bool) {
+	let workspace = try_fatal!(try_fatal!(WorkspaceList::get()).lookup(name));
+	
+	let projects = workspace.get_project_list().map(|list| list.get_project_names().len()).unwrap_or(0);
+	// The size is only informational, so an unreadable file does not prevent the removal
+	let size = workspace.get_size().map(format_size).unwrap_or_else(|_| "unknown size".to_owned());
+	let summary = format!("'{}' with {} projects ({}) in '{}'", workspace.name(), projects, size, workspace.path());
+	
+	if dry_run {
+		if delete_files {
+			println!("Would remove the workspace {} and delete its folder", summary);
+		} else {
+			println!("Would remove the workspace {}, keeping its folder", summary);
+		}
+		return;
+	}
+	
+	let message = if delete_files {
+		format!("Are you sure you want to remove the workspace {} and delete its folder? This is an irreversible action!", summary)
+	} else {
+		format!("Are you sure you want to remove the workspace {}? Its folder is kept", summary)
+	};
+	
+	if !get_confirmation(&message) {
+		println!("Removal of workspace aborted!");
+		return;
+	}
+	
+	try_fatal!(workspace.remove(delete_files));
+	
+	println!("Workspace removed!");
+	if !delete_files {
+		println!("The folder '{}' remains on disk", workspace.path());
+	}
+}
+
+
+/// Formats a number of bytes with a binary unit: '1.5 MiB'
+fn format_size(bytes: u64) -> String {
+	const UNITS: [&str; 5] = ["bytes", "KiB", "MiB", "GiB", "TiB"];
+	
+	let mut size = bytes as f64;
+	let mut unit = 0;
+	while size >= 1024.0 && unit < UNITS.len() - 1 {
+		size /= 1024.0;
+		unit += 1;
+	}
+	
+	if unit == 0 {
+		format!("{} {}", bytes, UNITS[0])
+	} else {
+		format!("{:.1} {}", size, UNITS[unit])
+	}
 }
 
 
-fn remove_project(name: &str) {
-	if get_confirmation(&format!("Are you sure you want to remove the project '{}'? This is an irreversible action!", name)) {
-		let mut current_workspace = get_current_workspace();
-		try_fatal!(current_workspace.remove_project(name));
+fn remove_project(name: &str, delete_files: bool, dry_run: bool) {
+	let mut current_workspace = get_current_workspace();
+	let name = &resolve_project_name(&current_workspace, name);
+	
+	if dry_run {
+		let prefix = format!("{}:", name.to_lowercase());
+		let subprojects = try_fatal!(current_workspace.get_project_list()).get_project_names().iter()
+			.filter(|other| other.to_lowercase().starts_with(&prefix))
+			.count();
+		let summary = format!("'{}' with {} subprojects in '{}'", name, subprojects, current_workspace.project_folder(name).display());
+		
+		if delete_files {
+			println!("Would remove the project {} and move its folder to the trash", summary);
+		} else {
+			println!("Would remove the project {}, keeping its folder", summary);
+		}
+		return;
+	}
+	
+	let message = if delete_files {
+		format!("Are you sure you want to remove the project '{}'? Its folder is moved to the trash", name)
+	} else {
+		format!("Are you sure you want to remove the project '{}'? Its folder is kept", name)
+	};
+	
+	if get_confirmation(&message) {
+		try_fatal!(current_workspace.remove_project(name, delete_files));
 		println!("Project removed!");
 	} else {
 		println!("Removal of project aborted!");
@@ -191,20 +867,276 @@ fn remove_project(name: &str) {
 }
 
 
-fn open_project(matches: &ArgMatches) {
-	use std::process::Command;
+fn move_project(matches: &ArgMatches) {
+	let mut workspace = get_current_workspace();
+	let from = &resolve_project_name(&workspace, matches.value_of("from").unwrap());
+	let to = try_fatal!(workspace.move_project(from, matches.value_of("to").unwrap()));
 	
+	println!("Project '{}' moved to '{}'!", from, to);
+}
+
+
+fn open_project(matches: &ArgMatches) {
 	let name = matches.value_of("name").unwrap();
 	
-	let current_workspace = get_current_workspace();
+	let mut current_workspace = get_current_workspace();
+	let name = &resolve_project_name(&current_workspace, name);
+	
+	try_fatal!(open_project_with(&mut current_workspace, name, matches.value_of("with")));
+}
+
+
+/// Opens a project with a command, or else its configured editor, and records it as recently opened
+/// once the command has been launched
+fn open_project_with(workspace: &mut Workspace, name: &str, command: Option<&str>) -> rpm::Result<()> {
+	let project_path = workspace.get_project_path(name)?;
+	
+	let opener = match command {
+		Some(command) => Opener::new(command),
+		None => match workspace.get_project_editor(name)? {
+			Some(command) => Opener::new(&command),
+			None => Opener::platform_default(),
+		}
+	};
+	
+	opener.open(&project_path)?;
+	
+	workspace.edit_project(name, |project| project.mark_opened())?;
+	
+	History::update(|history| {
+		history.add_project(workspace.name(), name);
+		Ok(())
+	})
+}
+
+
+fn configure_editor(matches: &ArgMatches) {
+	let editor = if matches.is_present("unset") {
+		None
+	} else {
+		match matches.value_of("command") {
+			Some(command) => Some(command.to_owned()),
+			None => return display_editor(matches),
+		}
+	};
+	
+	if let Some(name) = matches.value_of("project") {
+		let mut workspace = get_current_workspace();
+		let name = &resolve_project_name(&workspace, name);
+		try_fatal!(workspace.set_project_editor(name, editor));
+	} else if matches.is_present("workspace") {
+		let mut workspace = get_current_workspace();
+		try_fatal!(workspace.set_editor(editor));
+	} else {
+		let mut settings = try_fatal!(Settings::get());
+		settings.set_editor(editor);
+		try_fatal!(settings.save());
+	}
+}
+
+
+fn display_editor(matches: &ArgMatches) {
+	let editor = if let Some(name) = matches.value_of("project") {
+		let workspace = get_current_workspace();
+		try_fatal!(workspace.get_project_editor(&resolve_project_name(&workspace, name)))
+	} else if matches.is_present("workspace") {
+		let workspace = get_current_workspace();
+		match workspace.editor() {
+			Some(editor) => Some(editor.to_owned()),
+			None => try_fatal!(Settings::get()).editor().map(|editor| editor.to_owned()),
+		}
+	} else {
+		try_fatal!(Settings::get()).editor().map(|editor| editor.to_owned())
+	};
+	
+	match editor {
+		Some(editor) => println!("Editor: '{}'", editor),
+		None => println!("Editor: '{}' (default)", Opener::platform_default().command()),
+	}
+}
+
+
+
+fn adopt_project(matches: &ArgMatches) {
+	use std::path::Path;
+	
+	let directory = Path::new(matches.value_of("directory").unwrap());
+	let name = match matches.value_of("name") {
+		Some(name) => name.to_owned(),
+		None => match directory.canonicalize().unwrap_or_else(|_| directory.to_path_buf()).file_name() {
+			Some(name) => name.to_string_lossy().into_owned(),
+			None => fail_with_message(&format!("Error: Can not name a project after '{}', use '--as <name>'", directory.display())),
+		},
+	};
+	
+	let mut workspace = get_current_workspace();
+	try_fatal!(workspace.adopt_project(directory, &name, matches.is_present("link")));
+	
+	println!("Project '{}' added!", name);
+}
+
+
+fn clone_project(matches: &ArgMatches) {
+	let url = matches.value_of("url").unwrap();
+	let name = match matches.value_of("name").or_else(|| rpm::git::repository_name(url)) {
+		Some(name) => name,
+		None => fail_with_message(&format!("Error: Can not name a project after '{}', give it a name", url)),
+	};
+	
+	let mut workspace = get_current_workspace();
+	let name = try_fatal!(workspace.clone_project(url, name));
+	
+	println!("Project '{}' cloned from '{}'!", name, url);
+}
+
+
+fn scan_projects(matches: &ArgMatches) {
+	let mut workspace = get_current_workspace();
+	let folders = try_fatal!(workspace.get_untracked_folders());
+	
+	if folders.is_empty() {
+		println!("No untracked folders");
+		return;
+	}
+	
+	println!("Untracked folders");
+	for folder in folders.iter() {
+		println!("    {}", folder);
+	}
+	
+	if !matches.is_present("yes") && !get_confirmation(&format!("Add {} folders as projects?", folders.len())) {
+		println!("No projects added!");
+		return;
+	}
+	
+	for folder in folders.iter() {
+		try_fatal!(workspace.register_project(folder));
+		println!("Project '{}' added!", folder);
+	}
+}
+
+
+fn run_doctor(matches: &ArgMatches) {
+	let problems = try_fatal!(doctor::diagnose());
 	
-	let project_path = try_fatal!(current_workspace.get_project_path(name));
+	if problems.is_empty() {
+		println!("No problems found");
+		return;
+	}
+	
+	let fix = matches.is_present("fix");
+	let mut remaining = 0;
+	
+	println!("Problems");
+	for problem in problems.iter() {
+		println!("    {}", problem);
+		
+		if !fix {
+			remaining += 1;
+		} else if !problem.is_fixable() {
+			println!("        Can not be repaired automatically");
+			remaining += 1;
+		} else if let Err(err) = problem.fix() {
+			println!("        Failed to repair: {}", err);
+			remaining += 1;
+		} else {
+			println!("        Repaired");
+		}
+	}
 	
-	if cfg!(target_os = "windows") {
-		if let Err(e) = Command::new("explorer")
-			.arg(&project_path)
-			.spawn() {
-			fail_with_message(&format!("Command Error: {}", e));
+	if remaining > 0 {
+		if !fix {
+			println!("Run 'rpm doctor --fix' to repair them");
 		}
+		std::process::exit(1);
+	}
+}
+
+
+fn manage_trash(matches: &ArgMatches) {
+	match matches.subcommand() {
+		("list", Some(_)) => list_trash(),
+		("restore", Some(m)) => restore_project(m.value_of("name").unwrap()),
+		("empty", Some(m)) => empty_trash(m.is_present("yes")),
+		_ => fail_with_message("Error: Invalid argument parameters"),
+	}
+}
+
+
+fn list_trash() {
+	let trash = try_fatal!(get_current_workspace().get_trash());
+	
+	if trash.entries().is_empty() {
+		println!("The trash is empty");
+		return;
+	}
+	
+	println!("Trash");
+	for entry in trash.entries().iter().rev() {
+		println!("{} (removed {}, id '{}')", entry.name(), time::format_elapsed(entry.removed()), entry.id());
+	}
+}
+
+
+fn restore_project(name: &str) {
+	let mut workspace = get_current_workspace();
+	let name = try_fatal!(workspace.restore_project(name));
+	println!("Project '{}' restored!", name);
+}
+
+
+fn empty_trash(yes: bool) {
+	if !yes && !get_confirmation("Are you sure you want to permanently delete all projects in the trash? This is an irreversible action!") {
+		println!("Emptying of the trash aborted!");
+		return;
+	}
+	
+	let mut workspace = get_current_workspace();
+	let count = try_fatal!(workspace.empty_trash());
+	println!("{} projects deleted!", count);
+}
+
+
+fn manage_templates(matches: &ArgMatches) {
+	match matches.subcommand() {
+		("add", Some(m)) => add_template(m.value_of("name").unwrap(), Path::new(m.value_of_os("directory").unwrap())),
+		("list", Some(_)) => list_templates(),
+		("remove", Some(m)) => remove_template(m.value_of("name").unwrap()),
+		("show", Some(m)) => show_template(m.value_of("name").unwrap()),
+		_ => fail_with_message("Error: Invalid argument parameters"),
+	}
+}
+
+
+fn add_template(name: &str, directory: &Path) {
+	try_fatal!(TemplateList::update(|template_list| template_list.add(name, directory)));
+	println!("Template '{}' added!", name);
+}
+
+
+fn list_templates() {
+	let template_list = try_fatal!(TemplateList::get());
+	
+	if template_list.templates().is_empty() {
+		println!("No templates");
+		return;
 	}
+	
+	println!("Templates");
+	for template in template_list.templates() {
+		println!("{} (added {}, from '{}')", template.name(), time::format_date(template.added()), template.source());
+	}
+}
+
+
+fn remove_template(name: &str) {
+	try_fatal!(TemplateList::remove(name));
+	println!("Template '{}' removed!", name);
+}
+
+
+fn show_template(name: &str) {
+	let template_list = try_fatal!(TemplateList::get());
+	let template = try_fatal!(template_list.lookup(name));
+	println!("{}", template);
 }
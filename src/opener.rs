@@ -0,0 +1,226 @@
+use std::env;
+use std::process::Command;
+
+use error::{Error, Result};
+
+
+/// A command line used to open a project folder, such as 'code {path}' or '$EDITOR {path}'
+pub struct Opener {
+	command: String,
+	check_status: bool
+}
+
+
+impl Opener {
+	/// Creates an opener from a command template
+	pub fn new(command: &str) -> Self {
+		Opener {
+			command: command.to_owned(),
+			check_status: true
+		}
+	}
+	
+	
+	/// The program used to open folders when no editor has been configured
+	pub fn platform_default() -> Self {
+		if cfg!(target_os = "windows") {
+			// Explorer reports a failure exit code even when it succeeds
+			Opener {
+				command: "explorer {path}".to_owned(),
+				check_status: false
+			}
+		} else if cfg!(target_os = "macos") {
+			Opener::new("open {path}")
+		} else {
+			Opener::new("xdg-open {path}")
+		}
+	}
+	
+	
+	/// Returns the command template of this opener
+	pub fn command(&self) -> &str {
+		&self.command
+	}
+	
+	
+	/// Opens a path with this opener, waiting for the program to finish. If the program is an
+	/// environment variable which is not set, such as '$EDITOR', the platform's default program is
+	/// used instead
+	pub fn open(&self, path: &str) -> Result<()> {
+		let words = split_words(&self.command)?;
+		if words.first().is_some_and(|program| program.contains('$') && expand_environment(program).trim().is_empty()) {
+			return Opener::platform_default().open(path);
+		}
+		
+		let arguments = self.arguments(path)?;
+		
+		let status = match Command::new(&arguments[0]).args(&arguments[1..]).status() {
+			Ok(status) => status,
+			Err(e) => return Err(Error::Command {
+				command: self.command.clone(),
+				message: format!("Failed to launch '{}': {}\nConfigure another editor with 'rpm editor <command>'", arguments[0], e)
+			}),
+		};
+		
+		if self.check_status && !status.success() {
+			return Err(Error::Command {
+				command: self.command.clone(),
+				message: format!("Exited with {}", status)
+			});
+		}
+		
+		Ok(())
+	}
+	
+	
+	/// Splits the command template into arguments, expanding environment variables and '{path}'.
+	/// The path is appended as the last argument if the template does not contain '{path}'
+	fn arguments(&self, path: &str) -> Result<Vec<String>> {
+		let words = split_words(&self.command)?;
+		
+		let mut contains_path = false;
+		let mut arguments = Vec::new();
+		
+		for word in words.iter() {
+			if word.contains("{path}") {
+				contains_path = true;
+			}
+			
+			let argument = expand_environment(word).replace("{path}", path);
+			
+			// Variables such as '$EDITOR' may contain several words
+			if word.starts_with('$') && !word.contains("{path}") {
+				arguments.extend(argument.split_whitespace().map(|s| s.to_owned()));
+			} else if !argument.is_empty() {
+				arguments.push(argument);
+			}
+		}
+		
+		if arguments.is_empty() {
+			return Err(Error::Invalid(format!("The editor command '{}' is empty after expanding environment variables!", self.command)));
+		}
+		
+		if !contains_path {
+			arguments.push(path.to_owned());
+		}
+		
+		Ok(arguments)
+	}
+}
+
+
+/// Splits a command line into words, honoring single and double quotes
+fn split_words(command: &str) -> Result<Vec<String>> {
+	let mut words = Vec::new();
+	let mut word = String::new();
+	let mut in_word = false;
+	let mut quote = None;
+	
+	for c in command.chars() {
+		match quote {
+			Some(q) if c == q => quote = None,
+			Some(_) => word.push(c),
+			None if c == '"' || c == '\'' => {
+				quote = Some(c);
+				in_word = true;
+			},
+			None if c.is_whitespace() => {
+				if in_word {
+					words.push(word.clone());
+					word.clear();
+					in_word = false;
+				}
+			},
+			None => {
+				word.push(c);
+				in_word = true;
+			},
+		}
+	}
+	
+	if quote.is_some() {
+		return Err(Error::Invalid(format!("Unterminated quote in editor command '{}'", command)));
+	}
+	
+	if in_word {
+		words.push(word);
+	}
+	
+	Ok(words)
+}
+
+
+/// Replaces '$VARIABLE' and '${VARIABLE}' with the value of the environment variable
+fn expand_environment(text: &str) -> String {
+	let mut result = String::new();
+	let mut chars = text.chars().peekable();
+	
+	while let Some(c) = chars.next() {
+		if c != '$' {
+			result.push(c);
+			continue;
+		}
+		
+		let mut name = String::new();
+		if chars.peek() == Some(&'{') {
+			chars.next();
+			for c in chars.by_ref() {
+				if c == '}' {
+					break;
+				}
+				name.push(c);
+			}
+		} else {
+			while let Some(&c) = chars.peek() {
+				if c.is_alphanumeric() || c == '_' {
+					name.push(c);
+					chars.next();
+				} else {
+					break;
+				}
+			}
+		}
+		
+		if name.is_empty() {
+			result.push('$');
+		} else {
+			result += &env::var(&name).unwrap_or_default();
+		}
+	}
+	
+	result
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	#[test]
+	fn split_words_honors_quotes() {
+		assert_eq!(split_words("code  --wait {path}").unwrap(), vec!["code", "--wait", "{path}"]);
+		assert_eq!(split_words("'my editor' \"a b\" ''").unwrap(), vec!["my editor", "a b", ""]);
+		assert!(split_words("code 'unterminated").is_err());
+	}
+	
+	
+	#[test]
+	fn expand_environment_replaces_variables() {
+		env::set_var("RPM_TEST_EDITOR", "vim");
+		env::remove_var("RPM_TEST_UNSET");
+		
+		assert_eq!(expand_environment("$RPM_TEST_EDITOR"), "vim");
+		assert_eq!(expand_environment("${RPM_TEST_EDITOR}-x"), "vim-x");
+		assert_eq!(expand_environment("$RPM_TEST_UNSET"), "");
+		assert_eq!(expand_environment("cost $5"), "cost ");
+		assert_eq!(expand_environment("a $ b"), "a $ b");
+	}
+	
+	
+	#[test]
+	fn arguments_append_the_path() {
+		assert_eq!(Opener::new("code --wait").arguments("/p").unwrap(), vec!["code", "--wait", "/p"]);
+		assert_eq!(Opener::new("term -e 'cd {path}'").arguments("/p").unwrap(), vec!["term", "-e", "cd /p"]);
+	}
+}
@@ -1,17 +1,51 @@
 
 use std::path::{Path, PathBuf};
 use std::fs;
-use std::io::{Write, Read, ErrorKind};
+use std::io::ErrorKind;
 
 use std::fmt;
 
-use serde_json;
+use database;
+use fuzzy;
+use error::{Error, Result};
+use time;
 
 
+/// A project and its subprojects, each project has a folder of the same name within its parent's folder
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Project {
 	name: String,
-	projects: Vec<Project>
+	projects: Vec<Project>,
+	
+	/// The command used to open this project, overrides the workspace's editor
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	editor: Option<String>,
+	
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	description: Option<String>,
+	
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	tags: Vec<String>,
+	
+	/// The kind of project, such as "rust", "music" or "web"
+	#[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+	project_type: Option<String>,
+	
+	/// Higher values are more important
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	priority: Option<i32>,
+	
+	/// The time the project was created, in seconds since the unix epoch
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	created: Option<u64>,
+	
+	/// The time the project was last opened, in seconds since the unix epoch
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	last_opened: Option<u64>,
+	
+	/// The url of the git repository the project was cloned from
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	remote: Option<String>
 }
 
 
@@ -27,24 +61,162 @@ impl Project {
 		
 		Project {
 			name: names[0].to_owned(),
-			projects
+			projects,
+			editor: None,
+			description: None,
+			tags: Vec::new(),
+			project_type: None,
+			priority: None,
+			created: Some(time::now()),
+			last_opened: None,
+			remote: None
+		}
+	}
+	
+	
+	/// Returns the name of this project, without the names of its parents
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+	
+	
+	/// Renames this project, keeping its subprojects
+	pub fn set_name(&mut self, name: &str) {
+		self.name = name.to_owned();
+	}
+	
+	
+	/// Returns the description of this project
+	pub fn description(&self) -> Option<&str> {
+		self.description.as_deref()
+	}
+	
+	
+	/// Returns the tags of this project
+	pub fn tags(&self) -> &[String] {
+		&self.tags
+	}
+	
+	
+	/// Returns the kind of project, such as "rust"
+	pub fn project_type(&self) -> Option<&str> {
+		self.project_type.as_deref()
+	}
+	
+	
+	/// Sets the description of this project
+	pub fn set_description(&mut self, description: Option<String>) {
+		self.description = description;
+	}
+	
+	
+	/// Adds a tag to this project, tags are case insensitive
+	pub fn add_tag(&mut self, tag: &str) {
+		if !self.has_tag(tag) {
+			self.tags.push(tag.to_owned());
 		}
 	}
 	
 	
+	/// Removes a tag from this project
+	pub fn remove_tag(&mut self, tag: &str) {
+		let tag = tag.to_lowercase();
+		self.tags.retain(|t| t.to_lowercase() != tag);
+	}
+	
+	
+	/// Returns true if this project has a tag
+	pub fn has_tag(&self, tag: &str) -> bool {
+		let tag = tag.to_lowercase();
+		self.tags.iter().any(|t| t.to_lowercase() == tag)
+	}
+	
+	
+	/// Sets the type of this project
+	pub fn set_project_type(&mut self, project_type: Option<String>) {
+		self.project_type = project_type;
+	}
+	
+	
+	/// Sets the priority of this project
+	pub fn set_priority(&mut self, priority: Option<i32>) {
+		self.priority = priority;
+	}
+	
+	
+	/// Returns the priority of this project, higher values are more important
+	pub fn priority(&self) -> Option<i32> {
+		self.priority
+	}
+	
+	
+	/// Returns the time this project was created, in seconds since the unix epoch
+	pub fn created(&self) -> Option<u64> {
+		self.created
+	}
+	
+	
+	/// Returns the time this project was last opened, in seconds since the unix epoch
+	pub fn last_opened(&self) -> Option<u64> {
+		self.last_opened
+	}
+	
+	
+	/// Records that this project was opened now
+	pub fn mark_opened(&mut self) {
+		self.last_opened = Some(time::now());
+	}
+	
+	
+	/// Returns the command used to open this project
+	pub fn editor(&self) -> Option<&str> {
+		self.editor.as_deref()
+	}
+	
+	
+	/// Sets the command used to open this project
+	pub fn set_editor(&mut self, editor: Option<String>) {
+		self.editor = editor;
+	}
+	
+	
+	/// Returns the url of the git repository this project was cloned from
+	pub fn remote(&self) -> Option<&str> {
+		self.remote.as_deref()
+	}
+	
+	
+	/// Sets the url of the git repository of this project
+	pub fn set_remote(&mut self, remote: Option<String>) {
+		self.remote = remote;
+	}
+	
+	
+	/// Returns the names along the chain of first subprojects: 'a:b:c' gives ["a", "b", "c"]
+	pub fn name_chain(&self) -> Vec<&str> {
+		let mut names = vec![self.name.as_str()];
+		
+		let mut project = self;
+		while let Some(child) = project.projects.first() {
+			names.push(&child.name);
+			project = child;
+		}
+		
+		names
+	}
+	
+	
 	/// Creates the project's folder
-	pub fn create_folder(&self, mut path: PathBuf) -> Result<(), String> {
+	pub fn create_folder(&self, mut path: PathBuf) -> Result<()> {
 		path.push(&self.name);
 		if let Err(e) = fs::create_dir_all(path.clone()) {
 			if e.kind() != ErrorKind::AlreadyExists {
-				return Err("Failed to create project directory!".to_owned());
+				return Err(Error::io(path, e));
 			}
 		}
 		
 		for project in self.projects.iter() {
-			if let Err(e) = project.create_folder(path.clone()) {
-				return Err(e);
-			}
+			project.create_folder(path.clone())?;
 		}
 		
 		Ok(())
@@ -52,17 +224,17 @@ impl Project {
 	
 	
 	/// Adds a new subproject to this project
-	pub fn add(&mut self, project: Project) -> Result<(), String> {
+	pub fn add(&mut self, project: Project) -> Result<()> {
 		let name = project.name.to_lowercase();
 		for proj in self.projects.iter_mut() {
 			if proj.name.to_lowercase() == name {
-				if project.projects.len() > 0 {
+				if !project.projects.is_empty() {
 					for p in project.projects {
-						proj.add(p);
+						proj.add(p)?;
 					}
 					return Ok(());
 				} else {
-					return Err("Project with that name already exists!".to_owned());
+					return Err(Error::already_exists("project", &project.name));
 				}
 			}
 		}
@@ -72,14 +244,14 @@ impl Project {
 	}
 	
 	
-	/// Remove a project from the project list
-	pub fn remove(&mut self, name: &str) -> Result<(), String> {
+	/// Remove a subproject from this project, returning it
+	pub fn remove(&mut self, name: &str) -> Result<Project> {
 		let name = name.to_lowercase().replace(|c|{c == '/' || c == '\\'}, ":");
 		let project_names: Vec<&str> = name.splitn(2, ':').collect();
 		
 		let mut remove_index = None;
 		for (index, project) in self.projects.iter_mut().enumerate() {
-			if project.name == project_names[0] {
+			if project.name.to_lowercase() == project_names[0] {
 				if project_names.len() > 1 {
 					return project.remove(project_names[1]);
 				} else {
@@ -88,16 +260,72 @@ impl Project {
 			}
 		}
 		
-		if let Some(index) = remove_index {
-			self.projects.remove(index);
-		} else {
-			return Err(format!("Failed to find project with matching name!"));
+		match remove_index {
+			Some(index) => Ok(self.projects.remove(index)),
+			None => Err(Error::not_found("project", &name)),
+		}
+	}
+}
+
+
+/// A line of the drawn project tree
+pub struct TreeLine<'a> {
+	/// The lines leading to the project from its parent, such as '│   └───'
+	pub prefix: String,
+	
+	/// The full name of the project, such as 'project1:project11'
+	pub name: String,
+	
+	/// The project on this line
+	pub project: &'a Project
+}
+
+
+/// A condition on projects, written as 'tag:rust', 'type:web' or 'name:music'
+pub enum ProjectFilter {
+	/// Projects with a tag
+	Tag(String),
+	
+	/// Projects of a type
+	Type(String),
+	
+	/// A project and its subprojects, given the project's full name
+	Name(String)
+}
+
+
+impl ProjectFilter {
+	/// Parses a filter such as 'tag:rust'
+	pub fn parse(text: &str) -> Result<ProjectFilter> {
+		let (kind, value) = match text.split_once(':') {
+			Some((kind, value)) if !value.is_empty() => (kind, value.to_lowercase()),
+			_ => return Err(Error::Invalid(format!("'{}' is not a filter, use 'tag:<tag>', 'type:<type>' or 'name:<project>'", text))),
+		};
+		
+		match kind {
+			"tag" => Ok(ProjectFilter::Tag(value)),
+			"type" => Ok(ProjectFilter::Type(value)),
+			"name" => Ok(ProjectFilter::Name(value.replace(['/', '\\'], ":"))),
+			_ => Err(Error::Invalid(format!("'{}' is not a filter, use 'tag:<tag>', 'type:<type>' or 'name:<project>'", text))),
+		}
+	}
+	
+	
+	/// Returns true if a project matches this filter, given the project's full name
+	pub fn matches(&self, name: &str, project: &Project) -> bool {
+		match *self {
+			ProjectFilter::Tag(ref tag) => project.has_tag(tag),
+			ProjectFilter::Type(ref project_type) => project.project_type().is_some_and(|t| t.to_lowercase() == *project_type),
+			ProjectFilter::Name(ref parent) => {
+				let name = name.to_lowercase();
+				name == *parent || name.starts_with(&(parent.clone() + ":"))
+			},
 		}
-		Ok(())
 	}
 }
 
 
+/// The projects of a workspace, stored in the workspace's project database
 #[derive(Serialize, Deserialize)]
 pub struct ProjectList {
 	#[serde(default = "default_projects")]
@@ -110,61 +338,46 @@ fn default_projects() -> Vec<Project> {
 
 
 impl ProjectList {
-	/// Load the current workspace list from a file
-	pub fn get(path: &str) -> Result<ProjectList, String> {
-		// Add file if it doesn't exist
-		if !Path::new(path).exists() {
-			return Err("Project database doesn't exist!".to_owned());
-		}
-		
-		let mut project_file = String::new();
-		match fs::File::open(path) {
-			Ok(mut file) => match file.read_to_string(&mut project_file) {
-				Ok(_) => (),
-				Err(_) => return Err("Failed to write to project database!".to_owned())
-			}
-			Err(_) => return Err("Failed to open project database!".to_owned()),
-		}
-		
-		// Deserialize list
-		let project_list: ProjectList = match serde_json::from_str(&project_file){
-			Ok(project_list) => project_list,
-			Err(_) => return Err("Failed to load project database!".to_owned()),
-		};
-		
-		Ok(project_list)
+	/// Load a project database from a file
+	pub fn get(path: &str) -> Result<ProjectList> {
+		database::load(Path::new(path))
 	}
 	
-
+	
 	/// Write the current project list to a file
-	pub fn save(&self, path: &str) -> Result<(), String> {
-		// Reseralize list
-		let project_list = serde_json::to_string_pretty(self).unwrap();
-		
-		match fs::File::create(&path) {
-			Ok(mut file) => match file.write_all(project_list.as_bytes()) {
-				Ok(_) => (),
-				Err(_) => return Err("Failed to write to project database!".to_owned())
-			}
-			Err(_) => return Err("Failed to create/open project database!".to_owned()),
-		}
+	pub fn save(&self, path: &str) -> Result<()> {
+		database::save(Path::new(path), self)
+	}
+	
+	
+	/// Loads a project database, applies a modification and saves it again. Other processes can
+	/// not modify the database in the meantime
+	pub fn update<T, F>(path: &str, modify: F) -> Result<T>
+		where F: FnOnce(&mut ProjectList) -> Result<T> {
+		let _lock = database::lock(Path::new(path))?;
 		
-		Ok(())
+		let mut project_list = ProjectList::get(path)?;
+		let result = modify(&mut project_list)?;
+		project_list.save(path)?;
+		
+		Ok(result)
 	}
 	
 	
 	/// Add a project to the project list
-	pub fn add(&mut self, project: Project) -> Result<(), String> {
+	pub fn add(&mut self, project: Project) -> Result<()> {
+		check_names(&project)?;
+		
 		let name = project.name.to_lowercase();
 		for proj in self.projects.iter_mut() {
 			if proj.name.to_lowercase() == name {
-				if project.projects.len() > 0 {
+				if !project.projects.is_empty() {
 					for p in project.projects {
 						proj.add(p)?;
 					}
 					return Ok(());
 				} else {
-					return Err("Project with that name already exists!".to_owned());
+					return Err(Error::already_exists("project", &project.name));
 				}
 			}
 		}
@@ -174,18 +387,16 @@ impl ProjectList {
 	}
 	
 	
-	/// Remove a project from the project list
-	pub fn remove_project(&mut self, name: &str) -> Result<(), String> {
-		if let Err(e) = self.exists(name) {
-			return Err(e);
-		}
+	/// Remove a project from the project list, returning it together with its subprojects
+	pub fn remove_project(&mut self, name: &str) -> Result<Project> {
+		self.exists(name)?;
 		
 		let name = name.to_lowercase().replace(|c|{c == '/' || c == '\\'}, ":");
 		let project_names: Vec<&str> = name.splitn(2, ':').collect();
 		
 		let mut remove_index = None;
 		for (index, project) in self.projects.iter_mut().enumerate() {
-			if project.name == project_names[0] {
+			if project.name.to_lowercase() == project_names[0] {
 				if project_names.len() > 1 {
 					return project.remove(project_names[1]);
 				} else {
@@ -194,15 +405,35 @@ impl ProjectList {
 			}
 		}
 		
-		if let Some(index) = remove_index {
-			self.projects.remove(index);
-		} else {
-			return Err(format!("Failed to find project with matching name!"));
+		match remove_index {
+			Some(index) => Ok(self.projects.remove(index)),
+			None => Err(Error::not_found("project", &name)),
 		}
+	}
+	
+	
+	/// Puts a previously removed project back at a name such as 'project1:project11', creating
+	/// its parent projects if they no longer exist
+	pub fn insert(&mut self, name: &str, project: Project) -> Result<()> {
+		check_name(name)?;
+		check_names(&project)?;
 		
-		Ok(())
+		if self.exists(name).is_ok() {
+			return Err(Error::already_exists("project", name));
+		}
+		
+		match name.rfind(':') {
+			Some(index) => {
+				let parent = &name[..index];
+				if self.exists(parent).is_err() {
+					self.add(Project::from_str(parent))?;
+				}
+				self.find_mut(parent)?.add(project)
+			},
+			None => self.add(project),
+		}
 	}
-
+	
 	
 	/// Returns all the names in the list, recursively separated by colons: ["project1", "project2", "project1:project11"]
 	pub fn get_project_names(&self) -> Vec<String> {
@@ -232,87 +463,325 @@ impl ProjectList {
 	}
 	
 	
-	/// Returns true if a project with a name exists
-	pub fn exists(&self, name: &str) -> Result<(), String> {
+	/// Returns the groups of project names which only differ by case
+	pub fn get_duplicate_names(&self) -> Vec<Vec<String>> {
+		let mut names = self.get_project_names();
+		names.sort_by_key(|name| name.to_lowercase());
+		
+		names.chunk_by(|a, b| a.to_lowercase() == b.to_lowercase())
+			.filter(|group| group.len() > 1)
+			.map(|group| group.to_vec())
+			.collect()
+	}
+	
+	
+	/// Returns the project with a name such as 'project1:project11'
+	pub fn find(&self, name: &str) -> Result<&Project> {
+		self.exists(name)?;
+		
 		let name = name.to_lowercase().replace(|c|{c == '/' || c == '\\'}, ":");
-		let names: Vec<String> = self.get_project_names().into_iter().map(|string|{string.to_lowercase()}).collect();
+		let mut projects = &self.projects;
+		let mut found = None;
 		
-		for proj_name in names.iter() {
-			if *proj_name == name {
-				return Ok(());
+		for part in name.split(':') {
+			match projects.iter().find(|project| project.name.to_lowercase() == part) {
+				Some(project) => {
+					projects = &project.projects;
+					found = Some(project);
+				},
+				None => return Err(Error::not_found("project", &name)),
 			}
 		}
 		
-		// No match was found, search for close matches
-		let mut matches = Vec::new();
+		found.ok_or_else(|| Error::not_found("project", &name))
+	}
+	
+	
+	/// Returns the name of a project with the case used in the project list: 'PROJECT1/project11' gives 'project1:project11'
+	pub fn canonical_name(&self, name: &str) -> Result<String> {
+		self.exists(name)?;
+		
+		let name = name.to_lowercase().replace(|c|{c == '/' || c == '\\'}, ":");
+		let mut projects = &self.projects;
+		let mut names = Vec::new();
 		
-		for proj_name in names.iter() {
-			if proj_name.contains(&name) || name.contains(proj_name) {
-				matches.push(proj_name);
+		for part in name.split(':') {
+			match projects.iter().find(|project| project.name.to_lowercase() == part) {
+				Some(project) => {
+					projects = &project.projects;
+					names.push(project.name.as_str());
+				},
+				None => return Err(Error::not_found("project", &name)),
 			}
 		}
 		
-		let mut text = String::from(format!("No projects with the name '{}' found!", name));
-		if matches.len() > 0 {
-			text += "\nDid you mean:";
+		Ok(names.join(":"))
+	}
+	
+	
+	/// Returns the project with a name such as 'project1:project11' for modification
+	pub fn find_mut(&mut self, name: &str) -> Result<&mut Project> {
+		self.exists(name)?;
+		
+		let name = name.to_lowercase().replace(|c|{c == '/' || c == '\\'}, ":");
+		let mut parts: Vec<&str> = name.split(':').collect();
+		let last = parts.pop().unwrap();
+		
+		let mut projects = &mut self.projects;
+		for part in parts {
+			match projects.iter_mut().find(|project| project.name.to_lowercase() == part) {
+				Some(project) => projects = &mut project.projects,
+				None => return Err(Error::not_found("project", &name)),
+			}
+		}
+		
+		match projects.iter_mut().find(|project| project.name.to_lowercase() == last) {
+			Some(project) => Ok(project),
+			None => Err(Error::not_found("project", &name)),
+		}
+	}
+	
+	
+	/// Returns the lines of the drawn project tree, each project after its parent
+	pub fn tree(&self) -> Vec<TreeLine<'_>> {
+		fn add_lines<'a>(lines: &mut Vec<TreeLine<'a>>, projects: &'a [Project], parent: Option<&str>, indent: &str) {
+			for (index, project) in projects.iter().enumerate() {
+				let last = index + 1 == projects.len();
+				let name = match parent {
+					Some(parent) => parent.to_owned() + ":" + &project.name,
+					None => project.name.clone()
+				};
+				
+				lines.push(TreeLine {
+					prefix: indent.to_owned() + if last { "└───" } else { "├───" },
+					name: name.clone(),
+					project
+				});
+				
+				let indent = indent.to_owned() + if last { "    " } else { "│   " };
+				add_lines(lines, &project.projects, Some(&name), &indent);
+			}
+		}
+		
+		let mut lines = Vec::new();
+		add_lines(&mut lines, &self.projects, None, "");
+		lines
+	}
+	
+	
+	/// Returns true if a project with a name exists
+	pub fn exists(&self, name: &str) -> Result<()> {
+		let name = name.to_lowercase().replace(|c|{c == '/' || c == '\\'}, ":");
+		let names = self.get_project_names();
+		
+		let matches: Vec<&String> = names.iter().filter(|proj_name| proj_name.to_lowercase() == name).collect();
+		match matches.len() {
+			0 => (),
+			1 => return Ok(()),
+			
+			// Names differing only by case
+			_ => return Err(Error::Ambiguous {
+				name,
+				candidates: matches.into_iter().cloned().collect()
+			}),
+		}
+		
+		// No match was found, search for close matches
+		Err(Error::NotFound {
+			kind: "project",
+			suggestions: fuzzy::rank(&name, &names),
+			name
+		})
+	}
+	
+	
+	/// Returns the full name of the project meant by a possibly abbreviated name. In order of
+	/// precedence, the name matches a project's full name, the last parts of a project's name
+	/// ('synth' matches 'music:synth'), or is a prefix of those parts ('mu:syn' matches
+	/// 'music:synth-pad'). Several matches in the same category are ambiguous
+	pub fn resolve(&self, name: &str) -> Result<String> {
+		let name = name.to_lowercase().replace(|c|{c == '/' || c == '\\'}, ":");
+		let names = self.get_project_names();
+		
+		let query: Vec<&str> = name.split(':').collect();
+		
+		// Compares the query to the last parts of a project's name
+		let matches_parts = |project: &String, exact: bool| {
+			let lowercase = project.to_lowercase();
+			let parts: Vec<&str> = lowercase.split(':').collect();
 			
-			for proposal in matches.iter() {
-				text += "\n";
-				text += proposal;
+			parts.len() >= query.len() && parts[parts.len() - query.len()..].iter().zip(query.iter())
+				.all(|(part, query)| if exact { part == query } else { part.starts_with(query) })
+		};
+		
+		let exact: Vec<&String> = names.iter().filter(|project| project.to_lowercase() == name).collect();
+		let trailing: Vec<&String> = names.iter().filter(|project| matches_parts(project, true)).collect();
+		let prefix: Vec<&String> = names.iter().filter(|project| matches_parts(project, false)).collect();
+		
+		for matches in [exact, trailing, prefix].iter() {
+			match matches.len() {
+				0 => (),
+				1 => return Ok(matches[0].clone()),
+				_ => {
+					let mut candidates: Vec<String> = matches.iter().map(|project| (*project).clone()).collect();
+					candidates.sort();
+					return Err(Error::Ambiguous {
+						name,
+						candidates
+					});
+				},
 			}
 		}
-		Err(text)
+		
+		Err(Error::NotFound {
+			kind: "project",
+			suggestions: fuzzy::rank(&name, &names),
+			name
+		})
 	}
 }
 
 
-impl fmt::Display for ProjectList {
+/// Fails if a part of a name such as 'project1:project11' can not be the name of a folder, so the
+/// folder of a project is always within the folder of its parent
+pub fn check_name(name: &str) -> Result<()> {
+	if name.split(':').all(is_valid_part) {
+		Ok(())
+	} else {
+		Err(invalid_name(name))
+	}
+}
+
+
+/// Fails if the name of a project or of one of its subprojects can not be the name of a folder
+fn check_names(project: &Project) -> Result<()> {
+	if !is_valid_part(&project.name) {
+		return Err(invalid_name(&project.name));
+	}
+	
+	project.projects.iter().try_for_each(check_names)
+}
+
+
+/// Returns true if a part of a project name can be the name of a folder
+fn is_valid_part(part: &str) -> bool {
+	!part.is_empty() && part != "." && part != ".." && !part.contains([':', '/', '\\'])
+}
+
+
+/// Returns the error for a name that can not be used for a project
+fn invalid_name(name: &str) -> Error {
+	Error::Invalid(format!("'{}' is not a valid project name, its parts must not be empty, '.' or '..' or contain path separators", name))
+}
+
+
+impl fmt::Display for Project {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		use std::collections::HashSet;
-		
-		let mut text = String::from("Projects\n");
+		write!(f, "Project '{}'", self.name)?;
 		
-		{
-			fn add_project(mut text: String, project: &Project, mut bar_levels: HashSet<usize>, depth: usize, last: bool) -> String {
-				
-				let mut vec = Vec::new();
-				for elem in bar_levels.iter() {
-					vec.push(elem.clone());
-				}
-				
-				for i in 0..depth {
-					text += if i + 1 == depth {
-						if last {
-							bar_levels.remove(&i);
-							"└───"
-						} else {
-							"├───"
-						}
-					} else if bar_levels.contains(&i) {
-						"│   "
-					} else {
-						"    "
-					}
-				}
-				
-				text += &project.name;
-				text += "\n";
-				
-				bar_levels.insert(depth);
-				
-				for (index, proj) in project.projects.iter().enumerate() {
-					text = add_project(text, proj, bar_levels.clone(), depth + 1, index == project.projects.len() - 1);
-				}
-				
-				text
-			};
-			
-			for (index, project) in self.projects.iter().enumerate() {
-				text = add_project(text, project, {let mut set = HashSet::new(); set.insert(0); set}, 1, index == self.projects.len() - 1);
-			}
+		if let Some(ref description) = self.description {
+			write!(f, "\nDescription: {}", description)?;
+		}
+		if let Some(ref project_type) = self.project_type {
+			write!(f, "\nType: {}", project_type)?;
+		}
+		if !self.tags.is_empty() {
+			write!(f, "\nTags: {}", self.tags.join(", "))?;
+		}
+		if let Some(priority) = self.priority {
+			write!(f, "\nPriority: {}", priority)?;
+		}
+		if let Some(created) = self.created {
+			write!(f, "\nCreated: {}", time::format_date(created))?;
+		}
+		if let Some(last_opened) = self.last_opened {
+			write!(f, "\nLast opened: {}", time::format_date(last_opened))?;
 		}
+		if let Some(ref editor) = self.editor {
+			write!(f, "\nEditor: {}", editor)?;
+		}
+		if let Some(ref remote) = self.remote {
+			write!(f, "\nRemote: {}", remote)?;
+		}
+		
+		Ok(())
+	}
+}
+
+
+impl fmt::Display for ProjectList {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "Projects")?;
 		
+		for line in self.tree() {
+			writeln!(f, "{}{}", line.prefix, line.project.name)?;
+		}
 		
-		write!(f, "{}", text)
+		Ok(())
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	fn project_list(names: &[&str]) -> ProjectList {
+		let mut list = ProjectList {
+			projects: Vec::new()
+		};
+		for name in names.iter() {
+			list.add(Project::from_str(name)).unwrap();
+		}
+		list
+	}
+	
+	
+	#[test]
+	fn resolve_prefers_exact_names() {
+		let list = project_list(&["synth", "music:synth"]);
+		assert_eq!(list.resolve("synth").unwrap(), "synth");
+		assert_eq!(list.resolve("MUSIC/synth").unwrap(), "music:synth");
+	}
+	
+	
+	#[test]
+	fn resolve_matches_trailing_parts_and_prefixes() {
+		let list = project_list(&["music:synth-pad", "music:drums", "code:rusty"]);
+		assert_eq!(list.resolve("drums").unwrap(), "music:drums");
+		assert_eq!(list.resolve("mu:syn").unwrap(), "music:synth-pad");
+		assert_eq!(list.resolve("rus").unwrap(), "code:rusty");
+	}
+	
+	
+	#[test]
+	fn resolve_reports_ambiguous_names() {
+		let list = project_list(&["music:synth", "archive:synth"]);
+		match list.resolve("synth") {
+			Err(Error::Ambiguous { candidates, .. }) => assert_eq!(candidates, vec!["archive:synth", "music:synth"]),
+			_ => panic!("'synth' should be ambiguous"),
+		}
+	}
+	
+	
+	#[test]
+	fn resolve_suggests_similar_names() {
+		let list = project_list(&["music:synth-pad"]);
+		match list.resolve("snyth") {
+			Err(Error::NotFound { suggestions, .. }) => assert_eq!(suggestions, vec!["music:synth-pad"]),
+			_ => panic!("'snyth' should not be found"),
+		}
+	}
+	
+	
+	#[test]
+	fn add_rejects_names_that_are_not_folders() {
+		let mut list = project_list(&["music"]);
+		for name in ["..", "music:.", "music::x", "music:", "./x"].iter() {
+			assert!(list.add(Project::from_str(name)).is_err(), "{}", name);
+		}
+		assert!(list.insert("music:..", Project::from_str("..")).is_err());
+		assert!(list.add(Project::from_str("music:synth.v2")).is_ok());
 	}
 }
@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+use config::data_directory;
+use database;
+use error::Result;
+
+
+/// Global preferences shared by all workspaces
+#[derive(Default, Serialize, Deserialize)]
+pub struct Settings {
+	/// The default command used to open projects
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	editor: Option<String>
+}
+
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+
+impl Settings {
+	/// Load the global settings from a file, the default settings are used if there is no file
+	pub fn get() -> Result<Settings> {
+		let settings_file_path = Settings::path()?;
+		
+		if !database::exists(&settings_file_path) {
+			return Ok(Settings::default());
+		}
+		
+		database::load(&settings_file_path)
+	}
+	
+	
+	/// Write the global settings to a file
+	pub fn save(&self) -> Result<()> {
+		database::save(&Settings::path()?, self)
+	}
+	
+	
+	/// Returns the default editor command
+	pub fn editor(&self) -> Option<&str> {
+		self.editor.as_deref()
+	}
+	
+	
+	/// Sets the default editor command
+	pub fn set_editor(&mut self, editor: Option<String>) {
+		self.editor = editor;
+	}
+	
+	
+	/// Return the path to the settings file
+	fn path() -> Result<PathBuf> {
+		let mut path = data_directory()?;
+		path.push(SETTINGS_FILE_NAME);
+		Ok(path)
+	}
+}
@@ -0,0 +1,253 @@
+use clap::Shell;
+
+use rpm::WorkspaceList;
+
+use args;
+
+
+/// Defines 'rcd' in bash and zsh. Errors of 'rpm path' are printed to stderr, so only a folder is
+/// ever captured
+const POSIX_INIT: &str = r#"# rpm shell integration, load it with: eval "$(rpm shell-init bash)"
+rcd() {
+	local target
+	target="$(command rpm path "$@")" || return
+	cd -- "$target"
+}
+"#;
+
+
+/// Defines 'rcd' in fish
+const FISH_INIT: &str = r#"# rpm shell integration, load it with: rpm shell-init fish | source
+function rcd --description 'Change to the folder of an rpm project'
+	set -l target (command rpm path $argv); or return
+	cd $target
+end
+"#;
+
+
+/// Completes the names of workspaces and projects in bash, everything else is completed by the
+/// generated function
+const BASH_NAMES: &str = r#"
+_rpm_names() {
+	local line="${COMP_LINE:0:COMP_POINT}"
+	# Bash splits words at colons, so the current word is taken from the line
+	local cur="${line##*[[:space:]]}"
+	local arguments=() word skip="" kind=""
+	for word in ${line%"$cur"}; do
+		if [[ -n $skip ]]; then
+			skip=""
+		elif [[ $word == --config || $word == --format || $word == -w || $word == --with ]]; then
+			skip=1
+		elif [[ $word != -* ]]; then
+			arguments+=("$word")
+		fi
+	done
+	
+	case "${arguments[*]:1}" in
+		open|path|mv|"edit project"|"remove project") kind=projects ;;
+		switch|"remove workspace") kind=workspaces ;;
+	esac
+	
+	if [[ -z $kind || $cur == -* ]]; then
+		_rpm "$@"
+		return
+	fi
+	
+	COMPREPLY=($(compgen -W "$(command rpm complete-names $kind)" -- "$cur"))
+	
+	# Only the part after the last colon is replaced
+	if [[ $cur == *:* && $COMP_WORDBREAKS == *:* ]]; then
+		local prefix="${cur%"${cur##*:}"}"
+		COMPREPLY=("${COMPREPLY[@]#"$prefix"}")
+	fi
+}
+
+complete -F _rpm_names -o bashdefault -o default rpm
+"#;
+
+
+/// Completes the names of workspaces and projects in zsh. The generated function is renamed to
+/// '_rpm_arguments' and completes everything else
+const ZSH_NAMES: &str = r#"
+_rpm() {
+	local -a arguments names
+	local word skip kind
+	for word in "${(@)words[2,CURRENT-1]}"; do
+		if [[ -n $skip ]]; then
+			skip=
+		elif [[ $word == (--config|--format|-w|--with) ]]; then
+			skip=1
+		elif [[ $word != -* ]]; then
+			arguments+=("$word")
+		fi
+	done
+	
+	case "${arguments[*]}" in
+		(open|path|mv|"edit project"|"remove project") kind=projects ;;
+		(switch|"remove workspace") kind=workspaces ;;
+	esac
+	
+	if [[ -n $kind && $PREFIX != -* ]]; then
+		names=("${(@f)$(command rpm complete-names $kind)}")
+		compadd -a names
+	else
+		_rpm_arguments "$@"
+	fi
+}
+
+if [ "$funcstack[1]" = "_rpm" ]; then
+	_rpm "$@"
+else
+	compdef _rpm rpm
+fi
+"#;
+
+
+/// Completes the names of workspaces and projects in fish
+const FISH_NAMES: &str = r#"
+complete -c rpm -n "__fish_using_command rpm edit" -f -a "project"
+complete -c rpm -n "__fish_using_command rpm remove" -f -a "project workspace"
+for command in "open" "path" "mv" "edit project" "remove project"
+	complete -c rpm -n "__fish_using_command rpm $command" -f -a "(command rpm complete-names projects)"
+end
+for command in "switch" "remove workspace"
+	complete -c rpm -n "__fish_using_command rpm $command" -f -a "(command rpm complete-names workspaces)"
+end
+"#;
+
+
+/// Completes the names of workspaces and projects in PowerShell, inserted into the generated script
+/// block before the completions are filtered
+const POWERSHELL_NAMES: &str = r#"
+        $arguments = @($commandAst.CommandElements | Select-Object -Skip 1 | %{ $_.ToString() } |
+            ?{ $_ -notlike '-*' -and $_ -ne $wordToComplete })
+        $kind = switch ($arguments -join ' ') {
+            { $_ -in 'open', 'path', 'mv', 'edit project', 'remove project' } { 'projects' }
+            { $_ -in 'switch', 'remove workspace' } { 'workspaces' }
+        }
+        if ($kind -and $wordToComplete -notlike '-*') {
+            $completions = @(rpm complete-names $kind)
+        }
+"#;
+
+
+/// Returns the script defining the 'rcd' function for a shell
+pub fn init_script(shell: &str) -> String {
+	match shell {
+		"fish" => FISH_INIT.to_owned(),
+		"zsh" => POSIX_INIT.replace("shell-init bash", "shell-init zsh"),
+		_ => POSIX_INIT.to_owned(),
+	}
+}
+
+
+/// Prints the completion script generated from the command line arguments, extended to complete
+/// the names of workspaces and projects
+pub fn print_completions(shell: &str) {
+	print!("{}", completions(shell));
+}
+
+
+/// Returns the completion script for a shell
+fn completions(shell: &str) -> String {
+	let generator = match shell {
+		"bash" => Shell::Bash,
+		"zsh" => Shell::Zsh,
+		"fish" => Shell::Fish,
+		_ => Shell::PowerShell,
+	};
+	
+	let mut script = Vec::new();
+	args::app().gen_completions_to("rpm", generator, &mut script);
+	let script = String::from_utf8_lossy(&script);
+	
+	match generator {
+		Shell::Bash => format!("{}{}", script, BASH_NAMES),
+		Shell::Zsh => {
+			let script = script.replacen("\n_rpm() {", "\n_rpm_arguments() {", 1);
+			format!("{}{}", script.trim_end().trim_end_matches("_rpm \"$@\""), ZSH_NAMES)
+		},
+		Shell::Fish => format!("{}{}", script, FISH_NAMES),
+		Shell::PowerShell => script.replacen("\n        $completions |", &format!("{}\n        $completions |", POWERSHELL_NAMES.trim_end()), 1),
+	}
+}
+
+
+/// Prints the names of all workspaces, or of all projects in the current workspace, one per line.
+/// Nothing is printed if they can not be read, as the output is used while completing
+pub fn print_names(kind: &str) {
+	let list = match WorkspaceList::get() {
+		Ok(list) => list,
+		Err(_) => return,
+	};
+	
+	let names = if kind == "workspaces" {
+		list.workspaces().iter().map(|workspace| workspace.name().to_owned()).collect()
+	} else {
+		list.current().and_then(|workspace| workspace.get_project_list())
+			.map(|project_list| project_list.get_project_names())
+			.unwrap_or_default()
+	};
+	
+	for name in names {
+		println!("{}", name);
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	#[test]
+	fn init_script_defines_rcd() {
+		for shell in ["bash", "zsh", "fish"].iter() {
+			let script = init_script(shell);
+			assert!(script.contains("rcd"), "{}", shell);
+			assert!(script.contains("command rpm path"), "{}", shell);
+			assert!(script.contains(&format!("rpm shell-init {}", shell)), "{}", shell);
+		}
+		
+		assert!(init_script("bash").contains("rcd() {"));
+		assert!(init_script("fish").contains("function rcd"));
+		assert!(!init_script("zsh").contains("shell-init bash"));
+	}
+	
+	
+	#[test]
+	fn bash_completions_complete_names() {
+		let script = completions("bash");
+		assert!(script.contains("_rpm() {"));
+		assert!(script.trim_end().ends_with("complete -F _rpm_names -o bashdefault -o default rpm"));
+	}
+	
+	
+	#[test]
+	fn zsh_completions_rename_generated_function() {
+		let script = completions("zsh");
+		assert!(script.starts_with("#compdef rpm"));
+		assert!(script.contains("\n_rpm_arguments() {"));
+		assert_eq!(script.matches("\n_rpm() {").count(), 1);
+		assert!(script.ends_with(ZSH_NAMES));
+		assert!(!script[..script.len() - ZSH_NAMES.len()].trim_end().ends_with("_rpm \"$@\""));
+	}
+	
+	
+	#[test]
+	fn fish_completions_complete_names() {
+		let script = completions("fish");
+		assert!(script.contains("function __fish_using_command"));
+		assert!(script.ends_with(FISH_NAMES));
+	}
+	
+	
+	#[test]
+	fn powershell_completions_insert_names_before_filtering() {
+		let script = completions("powershell");
+		let names = script.find("rpm complete-names $kind").unwrap();
+		let filter = script.find("\n        $completions |").unwrap();
+		assert!(names < filter);
+		assert!(script.contains("Register-ArgumentCompleter"));
+	}
+}
@@ -0,0 +1,477 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io::{self, Write, Read};
+
+use std::fmt;
+
+use config::data_directory;
+use database;
+use files;
+use error::{Error, Result};
+use time;
+
+
+/// A directory tree which is copied into new projects, expanding placeholders such as '{{name}}'
+/// in both file names and file contents
+pub struct Template {
+	name: String,
+	path: PathBuf
+}
+
+
+/// The values substituted for placeholders when a template is expanded
+#[derive(Default)]
+pub struct TemplateVariables {
+	values: HashMap<String, String>
+}
+
+
+impl TemplateVariables {
+	/// Creates an empty set of variables
+	pub fn new() -> Self {
+		TemplateVariables {
+			values: HashMap::new()
+		}
+	}
+	
+	
+	/// Sets the value of a variable
+	pub fn set(&mut self, name: &str, value: &str) {
+		self.values.insert(name.to_owned(), value.to_owned());
+	}
+	
+	
+	/// Replaces every known '{{variable}}' in a text, unknown variables are left untouched
+	pub fn expand(&self, text: &str) -> String {
+		let mut result = String::new();
+		let mut rest = text;
+		
+		while let Some(start) = rest.find("{{") {
+			result += &rest[..start];
+			rest = &rest[start..];
+			
+			let end = match rest.find("}}") {
+				Some(end) => end,
+				None => break,
+			};
+			
+			let variable = rest[2..end].trim();
+			match self.values.get(variable) {
+				Some(value) => result += value,
+				None => result += &rest[..end + 2],
+			}
+			
+			rest = &rest[end + 2..];
+		}
+		
+		result += rest;
+		result
+	}
+}
+
+
+impl Template {
+	/// Creates a template from an existing directory
+	pub fn from_path(name: &str, path: PathBuf) -> Result<Template> {
+		if !path.is_dir() {
+			return Err(Error::io(path, io::Error::new(io::ErrorKind::NotFound, "Template folder does not exist")));
+		}
+		
+		Ok(Template {
+			name: name.to_owned(),
+			path
+		})
+	}
+	
+	
+	/// Copies the template into a directory, expanding all variables
+	pub fn instantiate(&self, destination: &Path, variables: &TemplateVariables) -> Result<()> {
+		copy_expanded(&self.path, destination, variables)
+	}
+	
+	
+	/// Returns the paths of all files in this template, relative to the template root
+	pub fn files(&self) -> Result<Vec<PathBuf>> {
+		let mut files = Vec::new();
+		let mut folders = vec![PathBuf::new()];
+		
+		while let Some(folder) = folders.pop() {
+			let path = self.path.join(&folder);
+			let entries = fs::read_dir(&path).map_err(|e| Error::io(&path, e))?;
+			
+			for entry in entries.filter_map(|entry| entry.ok()) {
+				let relative = folder.join(entry.file_name());
+				if entry.path().is_dir() {
+					folders.push(relative);
+				} else {
+					files.push(relative);
+				}
+			}
+		}
+		
+		files.sort();
+		Ok(files)
+	}
+	
+	
+	/// Returns the names of all variables used in this template's file names and contents
+	pub fn variables(&self) -> Result<BTreeSet<String>> {
+		let mut variables = BTreeSet::new();
+		
+		for file in self.files()? {
+			find_variables(&file.to_string_lossy(), &mut variables);
+			
+			let mut contents = Vec::new();
+			if let Ok(mut file) = fs::File::open(self.path.join(&file)) {
+				if file.read_to_end(&mut contents).is_err() {
+					continue;
+				}
+			}
+			
+			if let Ok(text) = String::from_utf8(contents) {
+				find_variables(&text, &mut variables);
+			}
+		}
+		
+		Ok(variables)
+	}
+}
+
+
+impl fmt::Display for Template {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "Template '{}'", self.name)?;
+		writeln!(f, "Location: '{}'", self.path.display())?;
+		
+		writeln!(f, "Files:")?;
+		match self.files() {
+			Ok(files) => for file in files.iter() {
+				writeln!(f, "    {}", file.display())?;
+			},
+			Err(e) => writeln!(f, "    {}", e)?,
+		}
+		
+		write!(f, "Variables:")?;
+		if let Ok(variables) = self.variables() {
+			for variable in variables.iter() {
+				write!(f, "\n    {{{{{}}}}}", variable)?;
+			}
+		}
+		
+		Ok(())
+	}
+}
+
+
+/// A template registered in the global template store
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TemplateEntry {
+	name: String,
+	
+	/// The directory the template was originally copied from
+	#[serde(default)]
+	source: String,
+	
+	/// The time the template was added, in seconds since the unix epoch
+	#[serde(default)]
+	added: u64
+}
+
+
+/// The global store of templates, shared by all workspaces
+#[derive(Serialize, Deserialize)]
+pub struct TemplateList {
+	#[serde(default = "default_templates")]
+	templates: Vec<TemplateEntry>
+}
+
+const TEMPLATES_FILE_NAME: &str = "templates.json";
+const TEMPLATES_FOLDER_NAME: &str = "templates";
+
+fn default_templates() -> Vec<TemplateEntry> {
+	Vec::new()
+}
+
+
+impl TemplateList {
+	/// Load the global template list from a file
+	pub fn get() -> Result<TemplateList> {
+		let template_file_path = TemplateList::path()?;
+		
+		if !database::exists(&template_file_path) {
+			return Ok(TemplateList {
+				templates: Vec::new()
+			});
+		}
+		
+		database::load(&template_file_path)
+	}
+	
+	
+	/// Write the global template list to a file
+	pub fn save(&self) -> Result<()> {
+		database::save(&TemplateList::path()?, self)
+	}
+	
+	
+	/// Loads the template list, applies a modification and saves it again. Other processes can not
+	/// modify the list in the meantime
+	pub fn update<T, F>(modify: F) -> Result<T>
+		where F: FnOnce(&mut TemplateList) -> Result<T> {
+		let _lock = database::lock(&TemplateList::path()?)?;
+		
+		let mut template_list = TemplateList::get()?;
+		let result = modify(&mut template_list)?;
+		template_list.save()?;
+		
+		Ok(result)
+	}
+	
+	
+	/// Copies a directory into the template store and registers it under a name
+	pub fn add(&mut self, name: &str, source: &Path) -> Result<Template> {
+		if self.position(name).is_some() {
+			return Err(Error::already_exists("template", name));
+		}
+		
+		if !source.is_dir() {
+			return Err(Error::io(source, io::Error::new(io::ErrorKind::NotFound, "Template directory does not exist")));
+		}
+		
+		let source = source.canonicalize().map_err(|e| Error::io(source, e))?;
+		
+		let path = TemplateList::template_path(name)?;
+		if path.exists() {
+			return Err(Error::io(path, io::Error::new(io::ErrorKind::AlreadyExists, "Template folder already exists")));
+		}
+		
+		if let Err(e) = files::copy_folder(&source, &path) {
+			let _ = fs::remove_dir_all(&path);
+			return Err(e);
+		}
+		
+		self.templates.push(TemplateEntry {
+			name: name.to_owned(),
+			source: source.to_string_lossy().into_owned(),
+			added: time::now()
+		});
+		
+		Template::from_path(name, path)
+	}
+	
+	
+	/// Removes a template from the store. The stored copy is only deleted once the list has been
+	/// saved, so the list never names a template that is gone
+	pub fn remove(name: &str) -> Result<()> {
+		let path = TemplateList::update(|template_list| {
+			let index = match template_list.position(name) {
+				Some(index) => index,
+				None => return Err(Error::not_found("template", name)),
+			};
+			
+			let entry = template_list.templates.remove(index);
+			TemplateList::template_path(&entry.name)
+		})?;
+		
+		if path.exists() {
+			fs::remove_dir_all(&path).map_err(|e| Error::io(&path, e))?;
+		}
+		
+		Ok(())
+	}
+	
+	
+	/// Looks up a template from the store
+	pub fn lookup(&self, name: &str) -> Result<Template> {
+		match self.position(name) {
+			Some(index) => {
+				let name = &self.templates[index].name;
+				Template::from_path(name, TemplateList::template_path(name)?)
+			},
+			None => Err(Error::not_found("template", name)),
+		}
+	}
+	
+	
+	/// Returns the entries of all registered templates
+	pub fn templates(&self) -> &[TemplateEntry] {
+		&self.templates
+	}
+	
+	
+	/// Returns the index of a template with a name
+	fn position(&self, name: &str) -> Option<usize> {
+		let name = name.to_lowercase();
+		self.templates.iter().position(|template| template.name.to_lowercase() == name)
+	}
+	
+	
+	/// Return the path to the template list file
+	fn path() -> Result<PathBuf> {
+		let mut path = data_directory()?;
+		path.push(TEMPLATES_FILE_NAME);
+		Ok(path)
+	}
+	
+	
+	/// Return the path to the stored copy of a template
+	fn template_path(name: &str) -> Result<PathBuf> {
+		check_name(name)?;
+		
+		let mut path = data_directory()?;
+		path.push(TEMPLATES_FOLDER_NAME);
+		path.push(name);
+		Ok(path)
+	}
+}
+
+
+impl TemplateEntry {
+	/// Returns the name of this template
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+	
+	
+	/// Returns the directory this template was copied from
+	pub fn source(&self) -> &str {
+		&self.source
+	}
+	
+	
+	/// Returns the time this template was added
+	pub fn added(&self) -> u64 {
+		self.added
+	}
+}
+
+
+/// Recursively copies the contents of a folder, expanding variables in names and contents
+fn copy_expanded(source: &Path, destination: &Path, variables: &TemplateVariables) -> Result<()> {
+	fs::create_dir_all(destination).map_err(|e| Error::io(destination, e))?;
+	
+	let entries = fs::read_dir(source).map_err(|e| Error::io(source, e))?;
+	
+	for entry in entries {
+		let entry = entry.map_err(|e| Error::io(source, e))?;
+		
+		let file_name = entry.file_name();
+		let file_name = variables.expand(&file_name.to_string_lossy());
+		let mut target = PathBuf::from(destination);
+		target.push(&file_name);
+		
+		if entry.path().is_dir() {
+			copy_expanded(&entry.path(), &target, variables)?;
+		} else {
+			copy_file_expanded(&entry.path(), &target, variables)?;
+		}
+	}
+	
+	Ok(())
+}
+
+
+/// Copies a single file, expanding variables if the file contains text
+fn copy_file_expanded(source: &Path, destination: &Path, variables: &TemplateVariables) -> Result<()> {
+	if destination.exists() {
+		return Err(Error::io(destination, io::Error::new(io::ErrorKind::AlreadyExists, "File already exists")));
+	}
+	
+	let mut contents = Vec::new();
+	let mut file = fs::File::open(source).map_err(|e| Error::io(source, e))?;
+	file.read_to_end(&mut contents).map_err(|e| Error::io(source, e))?;
+	
+	// Binary files are copied as they are
+	let contents = match String::from_utf8(contents) {
+		Ok(text) => variables.expand(&text).into_bytes(),
+		Err(e) => e.into_bytes(),
+	};
+	
+	let mut file = fs::File::create(destination).map_err(|e| Error::io(destination, e))?;
+	file.write_all(&contents).map_err(|e| Error::io(destination, e))
+}
+
+
+/// Collects the names of all '{{variable}}' placeholders in a text
+fn find_variables(text: &str, variables: &mut BTreeSet<String>) {
+	let mut rest = text;
+	
+	while let Some(start) = rest.find("{{") {
+		rest = &rest[start + 2..];
+		
+		match rest.find("}}") {
+			Some(end) => {
+				variables.insert(rest[..end].trim().to_owned());
+				rest = &rest[end + 2..];
+			},
+			None => break,
+		}
+	}
+}
+
+
+/// Fails if a template name can not be used as the name of a folder in a template folder, so a
+/// template is never read or stored outside of it
+pub(crate) fn check_name(name: &str) -> Result<()> {
+	use std::path::Component;
+	
+	let mut components = Path::new(name).components();
+	let single = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+	
+	if !single || name.contains(['/', '\\']) {
+		return Err(Error::Invalid(format!("'{}' is not a valid template name, it must not be empty, '.' or '..' or contain path separators", name)));
+	}
+	
+	Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	
+	fn variables() -> TemplateVariables {
+		let mut variables = TemplateVariables::new();
+		variables.set("name", "synth");
+		variables.set("year", "2026");
+		variables
+	}
+	
+	
+	#[test]
+	fn expand_replaces_known_variables() {
+		assert_eq!(variables().expand("# {{name}} ({{ year }})"), "# synth (2026)");
+		assert_eq!(variables().expand("{{name}}{{name}}"), "synthsynth");
+	}
+	
+	
+	#[test]
+	fn expand_keeps_unknown_and_unterminated_placeholders() {
+		assert_eq!(variables().expand("{{author}} {{name}}"), "{{author}} synth");
+		assert_eq!(variables().expand("{{name"), "{{name");
+		assert_eq!(variables().expand("no placeholders"), "no placeholders");
+	}
+	
+	
+	#[test]
+	fn find_variables_collects_names() {
+		let mut found = BTreeSet::new();
+		find_variables("{{ name }} {{year}} {{name}} {{open", &mut found);
+		
+		let found: Vec<&str> = found.iter().map(|name| name.as_str()).collect();
+		assert_eq!(found, vec!["name", "year"]);
+	}
+	
+	
+	#[test]
+	fn check_name_rejects_paths() {
+		assert!(check_name("rust-cli").is_ok());
+		assert!(check_name("my template").is_ok());
+		
+		for name in ["", ".", "..", "../x", "a/b", "a\\b", "/x"].iter() {
+			assert!(check_name(name).is_err(), "{}", name);
+		}
+	}
+}
@@ -0,0 +1,63 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+/// Returns the current time in seconds since the unix epoch
+pub fn now() -> u64 {
+	match SystemTime::now().duration_since(UNIX_EPOCH) {
+		Ok(duration) => duration.as_secs(),
+		Err(_) => 0,
+	}
+}
+
+
+/// Formats a timestamp as a date in the format 'YYYY-MM-DD' (UTC)
+pub fn format_date(timestamp: u64) -> String {
+	let (year, month, day) = civil_from_days((timestamp / 86400) as i64);
+	format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+
+/// Returns the year of a timestamp (UTC)
+pub fn year(timestamp: u64) -> i64 {
+	civil_from_days((timestamp / 86400) as i64).0
+}
+
+
+/// Converts a number of days since the unix epoch into a (year, month, day) triple
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+	let z = days + 719468;
+	let era = (if z >= 0 { z } else { z - 146096 }) / 146097;
+	let day_of_era = z - era * 146097;
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let mp = (5 * day_of_year + 2) / 153;
+	let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+	let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+	
+	(year, month, day)
+}
+
+
+/// Describes how long ago a timestamp was, such as '5 minutes ago'
+pub fn format_elapsed(timestamp: u64) -> String {
+	let elapsed = now().saturating_sub(timestamp);
+	
+	let (amount, unit) = if elapsed < 60 {
+		return "just now".to_owned();
+	} else if elapsed < 3600 {
+		(elapsed / 60, "minute")
+	} else if elapsed < 86400 {
+		(elapsed / 3600, "hour")
+	} else if elapsed < 86400 * 30 {
+		(elapsed / 86400, "day")
+	} else {
+		return format_date(timestamp);
+	};
+	
+	if amount == 1 {
+		format!("1 {} ago", unit)
+	} else {
+		format!("{} {}s ago", amount, unit)
+	}
+}
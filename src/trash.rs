@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use database;
+use error::{Error, Result};
+use project::Project;
+use time;
+
+
+/// A project that was removed together with its folder
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+	/// The name of the project's folder within the trash folder
+	id: String,
+	
+	/// The name the project had, such as 'project1:project11'
+	name: String,
+	
+	/// The project and its subprojects, as they were stored in the project database
+	project: Project,
+	
+	/// The time the project was removed, in seconds since the unix epoch
+	removed: u64
+}
+
+
+impl TrashEntry {
+	/// Returns the name of the project's folder within the trash folder
+	pub fn id(&self) -> &str {
+		&self.id
+	}
+	
+	
+	/// Returns the name the project had, such as 'project1:project11'
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+	
+	
+	/// Returns the removed project
+	pub fn project(&self) -> &Project {
+		&self.project
+	}
+	
+	
+	/// Returns the time the project was removed, in seconds since the unix epoch
+	pub fn removed(&self) -> u64 {
+		self.removed
+	}
+}
+
+
+/// The removed projects of a workspace, stored in the manifest of the workspace's trash folder
+#[derive(Default, Serialize, Deserialize)]
+pub struct Trash {
+	#[serde(default)]
+	entries: Vec<TrashEntry>
+}
+
+
+impl Trash {
+	/// Load a trash manifest from a file, the trash is empty if there is no file
+	pub fn get(path: &Path) -> Result<Trash> {
+		if !database::exists(path) {
+			return Ok(Trash::default());
+		}
+		
+		database::load(path)
+	}
+	
+	
+	/// Write the trash manifest to a file
+	pub fn save(&self, path: &Path) -> Result<()> {
+		database::save(path, self)
+	}
+	
+	
+	/// Loads a trash manifest, applies a modification and saves it again. Other processes can not
+	/// modify the manifest in the meantime
+	pub fn update<T, F>(path: &Path, modify: F) -> Result<T>
+		where F: FnOnce(&mut Trash) -> Result<T> {
+		let _lock = database::lock(path)?;
+		
+		let mut trash = Trash::get(path)?;
+		let result = modify(&mut trash)?;
+		trash.save(path)?;
+		
+		Ok(result)
+	}
+	
+	
+	/// Returns all removed projects, the most recently removed last
+	pub fn entries(&self) -> &[TrashEntry] {
+		&self.entries
+	}
+	
+	
+	/// Adds a removed project, returning the entry with the folder name reserved for it
+	pub fn add(&mut self, name: &str, project: Project) -> TrashEntry {
+		let removed = time::now();
+		
+		let base = format!("{}-{}", removed, name.replace(':', "-"));
+		let mut id = base.clone();
+		let mut counter = 1;
+		while self.entries.iter().any(|entry| entry.id == id) {
+			counter += 1;
+			id = format!("{}-{}", base, counter);
+		}
+		
+		let entry = TrashEntry {
+			id,
+			name: name.to_owned(),
+			project,
+			removed
+		};
+		
+		self.entries.push(entry.clone());
+		entry
+	}
+	
+	
+	/// Takes an entry out of the trash, given its id or the name of the project. The most recently
+	/// removed project is taken if several have the same name
+	pub fn take(&mut self, name: &str) -> Result<TrashEntry> {
+		let lowercase = name.to_lowercase().replace(|c|{c == '/' || c == '\\'}, ":");
+		
+		let index = self.entries.iter().rposition(|entry| entry.id == name)
+			.or_else(|| self.entries.iter().rposition(|entry| entry.name.to_lowercase() == lowercase));
+		
+		match index {
+			Some(index) => Ok(self.entries.remove(index)),
+			None => Err(Error::NotFound {
+				kind: "removed project",
+				name: name.to_owned(),
+				suggestions: self.entries.iter()
+					.filter(|entry| entry.name.to_lowercase().contains(&lowercase))
+					.map(|entry| entry.name.clone())
+					.collect()
+			}),
+		}
+	}
+	
+	
+	/// Removes all entries from the trash, returning them
+	pub fn clear(&mut self) -> Vec<TrashEntry> {
+		self.entries.drain(..).collect()
+	}
+}
@@ -0,0 +1,715 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use getch::Getch;
+
+use rpm::{Error, Project, Result, Workspace, WorkspaceList};
+use rpm::fuzzy;
+use rpm::history::History;
+
+use open_project_with;
+
+
+/// The key bindings, displayed at the bottom of the screen
+const HELP: &str = "↑↓ move  ←→ switch  / search  enter open  n new  r rename  t tag  d remove  q quit";
+
+
+/// A key press
+enum Key {
+	Up,
+	Down,
+	Left,
+	Right,
+	Enter,
+	Backspace,
+	Tab,
+	Escape,
+	/// Ctrl-C, which does not stop rpm while the terminal is in raw mode
+	Interrupt,
+	Char(char),
+	Other
+}
+
+
+/// The list that receives the arrow keys
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+	Workspaces,
+	Projects
+}
+
+
+/// A project in the project list
+struct Row {
+	/// The full name of the project
+	name: String,
+	
+	/// The text displayed in the list
+	text: String,
+	
+	/// The description, type and tags of the project
+	details: String
+}
+
+
+/// The terminal in raw mode, showing the alternate screen until dropped
+struct Terminal {
+	getch: Option<Getch>
+}
+
+
+impl Terminal {
+	/// Switches to the alternate screen and stops echoing input
+	fn enter() -> Terminal {
+		let mut terminal = Terminal {
+			getch: None
+		};
+		terminal.resume();
+		terminal
+	}
+	
+	
+	/// Restores the normal screen, to run another program in the terminal
+	fn suspend(&mut self) {
+		if self.getch.take().is_some() {
+			print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+			let _ = io::stdout().flush();
+		}
+	}
+	
+	
+	/// Switches to the alternate screen again after suspending. Ctrl-C is read as a key instead of
+	/// stopping rpm, so the terminal is always restored, and reads give up after a tenth of a second
+	/// so a lone Esc can be told apart from an escape sequence
+	fn resume(&mut self) {
+		self.getch = Some(Getch::new());
+		if cfg!(not(windows)) {
+			// Getch restores the previous settings when it is dropped
+			let _ = Command::new("stty").args(["-isig", "min", "0", "time", "1"]).stdin(Stdio::inherit()).stderr(Stdio::null()).status();
+		}
+		print!("\x1b[?1049h\x1b[?25l");
+		let _ = io::stdout().flush();
+	}
+	
+	
+	/// Returns the number of rows and columns of the terminal
+	fn size(&self) -> (usize, usize) {
+		let output = Command::new("stty").arg("size").stdin(Stdio::inherit()).stderr(Stdio::null()).output();
+		
+		if let Ok(output) = output {
+			let text = String::from_utf8_lossy(&output.stdout);
+			let numbers: Vec<usize> = text.split_whitespace().filter_map(|word| word.parse().ok()).collect();
+			if numbers.len() == 2 && numbers[0] > 0 && numbers[1] > 0 {
+				return (numbers[0], numbers[1]);
+			}
+		}
+		
+		let variable = |name: &str, default: usize| env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default);
+		(variable("LINES", 24), variable("COLUMNS", 80))
+	}
+	
+	
+	/// Writes a screen to the terminal
+	fn draw(&self, screen: &str) {
+		let mut stdout = io::stdout();
+		let _ = stdout.write_all(screen.as_bytes());
+		let _ = stdout.flush();
+	}
+	
+	
+	/// Waits for a key press
+	fn read_key(&self) -> Result<Key> {
+		match self.getch {
+			Some(ref getch) => read_key(getch).map_err(|e| Error::Invalid(format!("Failed to read from the terminal: {}", e))),
+			None => Ok(Key::Other),
+		}
+	}
+}
+
+
+impl Drop for Terminal {
+	fn drop(&mut self) {
+		self.suspend();
+	}
+}
+
+
+/// Reads a key, decoding the escape sequences of the arrow keys
+#[cfg(not(windows))]
+fn read_key(_getch: &Getch) -> io::Result<Key> {
+	use std::io::Read;
+	
+	/// Reads a byte, None if none arrived before the read timed out
+	fn read_byte() -> io::Result<Option<u8>> {
+		let mut buffer = [0];
+		Ok(if io::stdin().read(&mut buffer)? == 0 { None } else { Some(buffer[0]) })
+	}
+	
+	let first = loop {
+		if let Some(byte) = read_byte()? {
+			break byte;
+		}
+	};
+	
+	// Getch swallows escape sequences, so it is only used to set up the terminal
+	Ok(match first {
+		27 => match read_byte()? {
+			None => Key::Escape,
+			Some(b'[') | Some(b'O') => match read_byte()? {
+				Some(b'A') => Key::Up,
+				Some(b'B') => Key::Down,
+				Some(b'C') => Key::Right,
+				Some(b'D') => Key::Left,
+				_ => Key::Other,
+			},
+			_ => Key::Other,
+		},
+		3 => Key::Interrupt,
+		b'\r' | b'\n' => Key::Enter,
+		8 | 127 => Key::Backspace,
+		b'\t' => Key::Tab,
+		byte if byte < 32 => Key::Other,
+		byte if byte < 128 => Key::Char(byte as char),
+		byte => {
+			// The first byte of a multi-byte character tells how many bytes follow
+			let mut bytes = vec![byte];
+			for _ in 1..byte.leading_ones() {
+				bytes.extend(read_byte()?);
+			}
+			match String::from_utf8(bytes).ok().and_then(|text| text.chars().next()) {
+				Some(c) => Key::Char(c),
+				None => Key::Other,
+			}
+		},
+	})
+}
+
+
+/// Reads a key, the arrow keys are reported as 224 followed by a key code
+#[cfg(windows)]
+fn read_key(getch: &Getch) -> io::Result<Key> {
+	Ok(match getch.getch()? {
+		224 => match getch.getch()? {
+			72 => Key::Up,
+			80 => Key::Down,
+			75 => Key::Left,
+			77 => Key::Right,
+			_ => Key::Other,
+		},
+		13 => Key::Enter,
+		8 => Key::Backspace,
+		9 => Key::Tab,
+		27 => Key::Escape,
+		3 => Key::Interrupt,
+		byte if byte >= 32 && byte < 127 => Key::Char(byte as char),
+		_ => Key::Other,
+	})
+}
+
+
+/// The workspaces and projects shown on the screen and the current selection
+struct Browser {
+	workspaces: Vec<Workspace>,
+	current: String,
+	workspace: usize,
+	
+	rows: Vec<Row>,
+	project: usize,
+	
+	pane: Pane,
+	
+	/// The text projects are filtered by, and whether it is being typed
+	search: Option<String>,
+	searching: bool,
+	
+	/// The result of the last action, shown instead of the key bindings
+	message: Option<String>
+}
+
+
+impl Browser {
+	/// Loads the workspaces, selecting the active one
+	fn new() -> Result<Browser> {
+		let mut browser = Browser {
+			workspaces: Vec::new(),
+			current: String::new(),
+			workspace: 0,
+			rows: Vec::new(),
+			project: 0,
+			pane: Pane::Projects,
+			search: None,
+			searching: false,
+			message: None
+		};
+		
+		browser.load_workspaces()?;
+		browser.workspace = browser.workspaces.iter()
+			.position(|workspace| workspace.name().to_lowercase() == browser.current.to_lowercase())
+			.unwrap_or(0);
+		browser.load_projects();
+		
+		Ok(browser)
+	}
+	
+	
+	/// Loads the list of workspaces again
+	fn load_workspaces(&mut self) -> Result<()> {
+		let list = WorkspaceList::get()?;
+		self.workspaces = list.workspaces().to_vec();
+		self.current = list.current_name().to_owned();
+		
+		if self.workspace >= self.workspaces.len() {
+			self.workspace = self.workspaces.len().saturating_sub(1);
+		}
+		
+		Ok(())
+	}
+	
+	
+	/// Loads the projects of the selected workspace, keeping the selected project if possible
+	fn load_projects(&mut self) {
+		let selected = self.selected_project();
+		self.reload_projects(selected.as_deref());
+	}
+	
+	
+	/// Loads the projects of the selected workspace and selects a project
+	fn reload_projects(&mut self, select: Option<&str>) {
+		self.rows.clear();
+		
+		let project_list = match self.selected_workspace().map(|workspace| workspace.get_project_list()) {
+			Some(Ok(project_list)) => project_list,
+			Some(Err(e)) => {
+				self.message = Some(e.to_string());
+				return;
+			},
+			None => return,
+		};
+		
+		let tree = project_list.tree();
+		
+		match self.search {
+			Some(ref search) if !search.is_empty() => {
+				let search = search.to_lowercase();
+				let mut matches: Vec<(usize, Row)> = tree.iter().filter_map(|line| {
+					fuzzy::subsequence_gaps(&search, &line.name.to_lowercase()).map(|gaps| (gaps, Row {
+						name: line.name.clone(),
+						text: line.name.clone(),
+						details: details(line.project)
+					}))
+				}).collect();
+				
+				matches.sort_by_key(|&(gaps, ref row)| (gaps, row.name.len()));
+				self.rows = matches.into_iter().map(|(_, row)| row).collect();
+			},
+			_ => {
+				self.rows = tree.iter().map(|line| Row {
+					name: line.name.clone(),
+					text: line.prefix.clone() + line.project.name(),
+					details: details(line.project)
+				}).collect();
+			},
+		}
+		
+		self.project = select
+			.and_then(|name| self.rows.iter().position(|row| row.name.to_lowercase() == name.to_lowercase()))
+			.unwrap_or(0);
+	}
+	
+	
+	/// Returns the workspace selected in the left list
+	fn selected_workspace(&self) -> Option<&Workspace> {
+		self.workspaces.get(self.workspace)
+	}
+	
+	
+	/// Returns the full name of the project selected in the right list
+	fn selected_project(&self) -> Option<String> {
+		self.rows.get(self.project).map(|row| row.name.clone())
+	}
+	
+	
+	/// Draws the screen
+	fn render(&self, (height, width): (usize, usize)) -> String {
+		let height = height.max(5);
+		let visible = height - 3;
+		
+		let longest = self.workspaces.iter().map(|workspace| workspace.name().chars().count()).max().unwrap_or(0);
+		let left_width = (longest + 3).max(12).min(width / 3);
+		let right_width = width.saturating_sub(left_width + 1);
+		
+		let mut screen = String::from("\x1b[H");
+		
+		let title = match self.selected_workspace() {
+			Some(workspace) => format!(" Projects in '{}'", workspace.name()),
+			None => " Projects".to_owned(),
+		};
+		let title = match self.search {
+			Some(ref search) => format!("{}  /{}{}", title, search, if self.searching { "_" } else { "" }),
+			None => title,
+		};
+		screen += &format!("\x1b[1m{}│{}\x1b[0m\r\n", fit(" Workspaces", left_width), fit(&title, right_width));
+		
+		let workspace_offset = offset(self.workspace, visible);
+		let project_offset = offset(self.project, visible);
+		
+		for line in 0..visible {
+			let index = workspace_offset + line;
+			match self.workspaces.get(index) {
+				Some(workspace) => {
+					let marker = if workspace.name().to_lowercase() == self.current.to_lowercase() { "* " } else { "  " };
+					let text = fit(&(marker.to_owned() + workspace.name()), left_width);
+					screen += &highlight(&text, index == self.workspace, self.pane == Pane::Workspaces);
+				},
+				None => screen += &fit("", left_width),
+			}
+			
+			screen += "│";
+			
+			let index = project_offset + line;
+			match self.rows.get(index) {
+				Some(row) => {
+					let text = fit(&(" ".to_owned() + &row.text), right_width);
+					screen += &highlight(&text, index == self.project, self.pane == Pane::Projects);
+				},
+				None if index == 0 && self.search.is_some() => screen += &fit(" No matching projects", right_width),
+				None if index == 0 => screen += &fit(" No projects, press 'n' to create one", right_width),
+				None => screen += &fit("", right_width),
+			}
+			
+			screen += "\r\n";
+		}
+		
+		let details = self.rows.get(self.project).map(|row| row.details.as_str()).unwrap_or("");
+		screen += &format!("{}\r\n", fit(details, width));
+		screen += &format!("\x1b[7m{}\x1b[0m", fit(self.message.as_deref().unwrap_or(HELP), width));
+		
+		screen
+	}
+	
+	
+	/// Handles a key press, returns false when the browser should close
+	fn handle_key(&mut self, key: Key, terminal: &mut Terminal) -> Result<bool> {
+		self.message = None;
+		
+		if let Key::Interrupt = key {
+			return Ok(false);
+		}
+		
+		if self.searching {
+			match key {
+				Key::Char(c) => self.search.get_or_insert_with(String::new).push(c),
+				Key::Backspace => {
+					let empty = self.search.as_ref().is_none_or(|search| search.is_empty());
+					if empty {
+						self.search = None;
+						self.searching = false;
+					} else if let Some(ref mut search) = self.search {
+						search.pop();
+					}
+				},
+				Key::Enter => self.searching = false,
+				Key::Escape => {
+					self.search = None;
+					self.searching = false;
+				},
+				Key::Up | Key::Down => return self.handle_key_unfiltered(key, terminal),
+				_ => (),
+			}
+			
+			self.reload_projects(None);
+			return Ok(true);
+		}
+		
+		self.handle_key_unfiltered(key, terminal)
+	}
+	
+	
+	/// Handles a key press while no search text is being typed
+	fn handle_key_unfiltered(&mut self, key: Key, terminal: &mut Terminal) -> Result<bool> {
+		match key {
+			Key::Up => match self.pane {
+				Pane::Workspaces if self.workspace > 0 => {
+					self.workspace -= 1;
+					self.reload_projects(None);
+				},
+				Pane::Projects if self.project > 0 => self.project -= 1,
+				_ => (),
+			},
+			
+			Key::Down => match self.pane {
+				Pane::Workspaces if self.workspace + 1 < self.workspaces.len() => {
+					self.workspace += 1;
+					self.reload_projects(None);
+				},
+				Pane::Projects if self.project + 1 < self.rows.len() => self.project += 1,
+				_ => (),
+			},
+			
+			Key::Left => self.pane = Pane::Workspaces,
+			Key::Right => self.pane = Pane::Projects,
+			Key::Tab => self.pane = if self.pane == Pane::Workspaces { Pane::Projects } else { Pane::Workspaces },
+			
+			Key::Backspace | Key::Escape if self.search.is_some() => {
+				self.search = None;
+				self.load_projects();
+			},
+			
+			Key::Enter => match self.pane {
+				Pane::Workspaces => self.switch_workspace()?,
+				Pane::Projects => self.open_project(terminal),
+			},
+			
+			Key::Char('/') => {
+				self.searching = true;
+				self.search.get_or_insert_with(String::new);
+			},
+			
+			Key::Char('n') => self.new_project(terminal)?,
+			Key::Char('r') => self.rename_project(terminal)?,
+			Key::Char('t') => self.tag_project(terminal)?,
+			Key::Char('d') => self.remove_project(terminal)?,
+			Key::Char('q') => return Ok(false),
+			
+			_ => (),
+		}
+		
+		Ok(true)
+	}
+	
+	
+	/// Makes the selected workspace the active one
+	fn switch_workspace(&mut self) -> Result<()> {
+		let workspace = match self.selected_workspace() {
+			Some(workspace) => workspace.clone(),
+			None => return Ok(()),
+		};
+		
+		workspace.set_active()?;
+		
+		History::update(|history| {
+			history.add_workspace(workspace.name());
+			Ok(())
+		})?;
+		
+		self.load_workspaces()?;
+		self.pane = Pane::Projects;
+		self.message = Some(format!("Switched to '{}'", workspace.name()));
+		Ok(())
+	}
+	
+	
+	/// Opens the selected project in its editor
+	fn open_project(&mut self, terminal: &mut Terminal) {
+		let (mut workspace, name) = match (self.selected_workspace(), self.selected_project()) {
+			(Some(workspace), Some(name)) => (workspace.clone(), name),
+			_ => return,
+		};
+		
+		// Editors may run in this terminal
+		terminal.suspend();
+		let result = open_project_with(&mut workspace, &name, None);
+		terminal.resume();
+		
+		self.message = Some(match result {
+			Ok(()) => format!("Opened '{}'", name),
+			Err(e) => e.to_string(),
+		});
+	}
+	
+	
+	/// Asks for a name and creates a project
+	fn new_project(&mut self, terminal: &mut Terminal) -> Result<()> {
+		let mut workspace = match self.selected_workspace() {
+			Some(workspace) => workspace.clone(),
+			None => return Ok(()),
+		};
+		
+		let name = match self.prompt(terminal, "New project: ", "")? {
+			Some(name) => name,
+			None => return Ok(()),
+		};
+		
+		self.report(workspace.add_project(Project::from_str(&name), None), format!("Created '{}'", name));
+		self.reload_projects(Some(&name));
+		Ok(())
+	}
+	
+	
+	/// Asks for a new name and renames or moves the selected project
+	fn rename_project(&mut self, terminal: &mut Terminal) -> Result<()> {
+		let (mut workspace, name) = match (self.selected_workspace(), self.selected_project()) {
+			(Some(workspace), Some(name)) => (workspace.clone(), name),
+			_ => return Ok(()),
+		};
+		
+		let new_name = match self.prompt(terminal, "Rename to: ", &name)? {
+			Some(new_name) => new_name,
+			None => return Ok(()),
+		};
+		
+		match workspace.move_project(&name, &new_name) {
+			Ok(new_name) => {
+				self.message = Some(format!("Renamed '{}' to '{}'", name, new_name));
+				self.reload_projects(Some(&new_name));
+			},
+			Err(e) => self.message = Some(e.to_string()),
+		}
+		
+		Ok(())
+	}
+	
+	
+	/// Asks for tags to add, or to remove when prefixed with '-', to the selected project
+	fn tag_project(&mut self, terminal: &mut Terminal) -> Result<()> {
+		let (mut workspace, name) = match (self.selected_workspace(), self.selected_project()) {
+			(Some(workspace), Some(name)) => (workspace.clone(), name),
+			_ => return Ok(()),
+		};
+		
+		let tags = match self.prompt(terminal, "Tags (prefix with '-' to remove): ", "")? {
+			Some(tags) => tags,
+			None => return Ok(()),
+		};
+		
+		let result = workspace.edit_project(&name, |project| {
+			for tag in tags.split_whitespace() {
+				if let Some(tag) = tag.strip_prefix('-') {
+					project.remove_tag(tag);
+				} else {
+					project.add_tag(tag);
+				}
+			}
+		});
+		
+		self.report(result.map(|_| ()), format!("Tagged '{}'", name));
+		self.load_projects();
+		Ok(())
+	}
+	
+	
+	/// Removes the selected project after confirmation, its folder is kept
+	fn remove_project(&mut self, terminal: &mut Terminal) -> Result<()> {
+		let (mut workspace, name) = match (self.selected_workspace(), self.selected_project()) {
+			(Some(workspace), Some(name)) => (workspace.clone(), name),
+			_ => return Ok(()),
+		};
+		
+		self.message = Some(format!("Remove '{}'? Its folder is kept (y/n)", name));
+		terminal.draw(&self.render(terminal.size()));
+		
+		match terminal.read_key()? {
+			Key::Char('y') | Key::Char('Y') => {
+				self.report(workspace.remove_project(&name, false), format!("Removed '{}'", name));
+				self.load_projects();
+			},
+			_ => self.message = None,
+		}
+		
+		Ok(())
+	}
+	
+	
+	/// Reads a line of text at the bottom of the screen. Returns None if the text is empty or the
+	/// prompt is cancelled with Esc or Ctrl-C
+	fn prompt(&mut self, terminal: &mut Terminal, label: &str, initial: &str) -> Result<Option<String>> {
+		let mut text = initial.to_owned();
+		
+		loop {
+			self.message = Some(format!("{}{}_", label, text));
+			terminal.draw(&self.render(terminal.size()));
+			
+			match terminal.read_key()? {
+				Key::Enter => break,
+				Key::Escape | Key::Interrupt => {
+					self.message = None;
+					return Ok(None);
+				},
+				Key::Backspace => {
+					text.pop();
+				},
+				Key::Char(c) => text.push(c),
+				_ => (),
+			}
+		}
+		
+		self.message = None;
+		let text = text.trim().to_owned();
+		Ok(if text.is_empty() { None } else { Some(text) })
+	}
+	
+	
+	/// Shows the outcome of an action
+	fn report(&mut self, result: Result<()>, success: String) {
+		self.message = Some(match result {
+			Ok(()) => success,
+			Err(e) => e.to_string(),
+		});
+	}
+}
+
+
+/// Returns the description, type and tags of a project on one line
+fn details(project: &Project) -> String {
+	let mut details = Vec::new();
+	
+	if let Some(description) = project.description() {
+		details.push(description.to_owned());
+	}
+	if let Some(project_type) = project.project_type() {
+		details.push(format!("[{}]", project_type));
+	}
+	if !project.tags().is_empty() {
+		details.push(project.tags().iter().map(|tag| format!("#{}", tag)).collect::<Vec<String>>().join(" "));
+	}
+	
+	details.join("  ")
+}
+
+
+/// Cuts or pads a text to a number of characters
+fn fit(text: &str, width: usize) -> String {
+	let mut fitted: String = text.chars().take(width).collect();
+	let length = fitted.chars().count();
+	fitted.extend((length..width).map(|_| ' '));
+	fitted
+}
+
+
+/// Highlights the selected line, brightly if its list receives the arrow keys
+fn highlight(text: &str, selected: bool, focused: bool) -> String {
+	match (selected, focused) {
+		(true, true) => format!("\x1b[7m{}\x1b[0m", text),
+		(true, false) => format!("\x1b[4m{}\x1b[0m", text),
+		_ => text.to_owned(),
+	}
+}
+
+
+/// Returns the first line to show so that the selected line is visible
+fn offset(selected: usize, visible: usize) -> usize {
+	if selected >= visible { selected + 1 - visible } else { 0 }
+}
+
+
+/// Runs the browser until it is closed with 'q'
+pub fn run() -> Result<()> {
+	if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+		return Err(Error::Invalid("'rpm tui' must be run in a terminal".to_owned()));
+	}
+	
+	let mut browser = Browser::new()?;
+	let mut terminal = Terminal::enter();
+	
+	loop {
+		terminal.draw(&browser.render(terminal.size()));
+		
+		let key = terminal.read_key()?;
+		if !browser.handle_key(key, &mut terminal)? {
+			break;
+		}
+	}
+	
+	Ok(())
+}
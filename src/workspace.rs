@@ -1,97 +1,310 @@
 
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::io::{Write, Read, ErrorKind};
+use std::io::{Write, ErrorKind};
 
 
-use project::{Project, ProjectList};
-
-use serde_json;
+use history::History;
+use project::{self, Project, ProjectList};
+use template::{self, Template, TemplateList, TemplateVariables};
+use trash::Trash;
+use settings::Settings;
+use config::data_directory;
+use files;
+use git;
+use database;
+use error::{Error, Result};
+use time;
 
+/// A folder containing a tree of projects, described by the project database in '.workspace/projects.json'
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Workspace {
 	name: String,
-	path: String
+	path: String,
+	
+	/// The command used to open projects in this workspace, overrides the global editor
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	editor: Option<String>
 }
 
 const WORKSPACE_PREFERENCE_FOLDER_NAME: &'static str = ".workspace";
 const WORKSPACE_PROJECT_DATABASE_NAME: &'static str = "projects.json";
 const WORKSPACE_PROJECTS_FOLDER_NAME: &'static str = "projects";
+const WORKSPACE_TEMPLATES_FOLDER_NAME: &str = "templates";
+const WORKSPACE_TRASH_FOLDER_NAME: &str = "trash";
+const WORKSPACE_TRASH_MANIFEST_NAME: &str = "manifest.json";
 
 
 impl Workspace {
 	
-	// Creates a new workspace with the specified name
-	pub fn new(name: &str, path: &str) -> Result<Workspace, String> {
+	/// Creates a new workspace with the specified name, adding it to the list of workspaces
+	pub fn new(name: &str, path: &str) -> Result<Workspace> {
 		let workspace = Workspace {
 			name: name.to_owned(),
-			path: path.to_owned()
+			path: path.to_owned(),
+			editor: None
 		};
 		
-		if let Err(e) = workspace.add_to_workspace_list() {
-			return Err(e);
-		}
+		workspace.add_to_workspace_list()?;
 		
-		if let Err(_) = fs::create_dir_all(path) {
-			return Err("Failed to create workspace directory!".to_owned());
-		}
+		fs::create_dir_all(path).map_err(|e| Error::io(path, e))?;
 		
-		if let Err(e) = workspace.create_preferences() {
-			return Err(e);
-		}
+		workspace.create_preferences()?;
 		
 		Ok(workspace)
 	}
 	
 	
 	/// Add this workspace to the list of workspaces
-	fn add_to_workspace_list(&self) -> Result<(), String> {
-		// Deserialize list
-		let mut workspaces = match WorkspaceList::get() {
-			Ok(workspace) => workspace,
-			Err(e) => return Err(e),
+	fn add_to_workspace_list(&self) -> Result<()> {
+		WorkspaceList::update(|workspaces| {
+			// Check if workspace with the same name already exists
+			if workspaces.lookup_index(&self.name).is_ok() {
+				return Err(Error::already_exists("workspace", &self.name));
+			}
+			
+			// Add the workspace to the list
+			workspaces.workspaces.push(self.clone());
+			Ok(())
+		})
+	}
+	
+	
+	/// Sets this workspace as the active one
+	pub fn set_active(&self) -> Result<()> {
+		WorkspaceList::update(|workspaces| {
+			workspaces.current = self.name.clone();
+			Ok(())
+		})
+	}
+	
+	
+	/// Returns the name of this workspace
+	pub fn name<'a>(&'a self) -> &'a str {
+		&self.name
+	}
+	
+	
+	/// Returns the path to the root of this workspace
+	pub fn path(&self) -> &str {
+		&self.path
+	}
+	
+	
+	/// Returns the command used to open projects in this workspace
+	pub fn editor(&self) -> Option<&str> {
+		self.editor.as_deref()
+	}
+	
+	
+	/// Sets the command used to open projects in this workspace
+	pub fn set_editor(&mut self, editor: Option<String>) -> Result<()> {
+		WorkspaceList::update(|workspaces| {
+			let index = workspaces.lookup_index(&self.name)?;
+			
+			self.editor = editor;
+			workspaces.workspaces[index] = self.clone();
+			Ok(())
+		})
+	}
+	
+	
+	/// Renames this workspace, it stays the active workspace if it was
+	pub fn rename(&mut self, name: &str) -> Result<()> {
+		if name.is_empty() {
+			return Err(Error::Invalid("The name of a workspace can not be empty".to_owned()));
+		}
+		
+		let old_name = self.name.clone();
+		
+		WorkspaceList::update(|workspaces| {
+			let index = workspaces.lookup_index(&self.name)?;
+			
+			// Changing the case of the name is allowed
+			if name.to_lowercase() != self.name.to_lowercase() && workspaces.lookup_index(name).is_ok() {
+				return Err(Error::already_exists("workspace", name));
+			}
+			
+			if workspaces.is_current(self) {
+				workspaces.current = name.to_owned();
+			}
+			
+			self.name = name.to_owned();
+			workspaces.workspaces[index] = self.clone();
+			Ok(())
+		})?;
+		
+		// The history is only informational, the workspace has been renamed either way
+		let _ = History::update(|history| {
+			history.rename_workspace(&old_name, name);
+			Ok(())
+		});
+		
+		Ok(())
+	}
+	
+	
+	/// Moves the folder of this workspace, including all projects, to a new path
+	pub fn relocate(&mut self, path: &str) -> Result<()> {
+		use std::io;
+		
+		WorkspaceList::update(|workspaces| {
+			let index = workspaces.lookup_index(&self.name)?;
+			
+			let target = Path::new(path);
+			if target.exists() {
+				return Err(Error::io(target, io::Error::new(io::ErrorKind::AlreadyExists, "The folder already exists")));
+			}
+			if target.starts_with(&self.path) {
+				return Err(Error::Invalid(format!("Can not move the workspace '{}' into itself", self.name)));
+			}
+			
+			if let Some(parent) = target.parent() {
+				fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+			}
+			files::move_folder(Path::new(&self.path), target)?;
+			
+			self.path = path.to_owned();
+			workspaces.workspaces[index] = self.clone();
+			Ok(())
+		})
+	}
+	
+	
+	/// Removes this workspace from the list of workspaces, optionally deleting its folder together
+	/// with all projects. Only folders containing a project database are deleted
+	pub fn remove(&self, delete_files: bool) -> Result<()> {
+		WorkspaceList::update(|workspaces| {
+			workspaces.remove(&self.name)?;
+			
+			let path = Path::new(&self.path);
+			if !delete_files || !path.exists() {
+				return Ok(());
+			}
+			
+			if !Path::new(&self.workspace_preferences_folder_path()).is_dir() {
+				return Err(Error::Invalid(format!("'{}' does not contain a workspace, it was not deleted", self.path)));
+			}
+			
+			fs::remove_dir_all(path).map_err(|e| Error::io(path, e))
+		})
+	}
+	
+	
+	/// Returns the total size of the files in this workspace's folder in bytes
+	pub fn get_size(&self) -> Result<u64> {
+		let path = Path::new(&self.path);
+		if !path.exists() {
+			return Ok(0);
+		}
+		
+		files::folder_size(path)
+	}
+	
+	
+	/// Creates a new workspace with the projects of this workspace. The project database is always
+	/// copied, the contents of the project folders only if 'copy_projects' is set, otherwise the
+	/// new workspace gets empty project folders
+	pub fn clone_to(&self, name: &str, path: &str, copy_projects: bool) -> Result<Workspace> {
+		use std::io;
+		
+		let workspace = Workspace {
+			name: name.to_owned(),
+			path: path.to_owned(),
+			editor: self.editor.clone()
 		};
 		
-		// Check if workspace with the same name already exists
-		if let Ok(_) = workspaces.lookup_index(&self.name) {
-			return Err(format!("Workspace with the name '{}' already exists", self.name).to_owned())
+		if WorkspaceList::get()?.lookup_index(name).is_ok() {
+			return Err(Error::already_exists("workspace", name));
 		}
 		
-		// Add the workspace to the list
-		workspaces.workspaces.push(self.clone());
+		let target = Path::new(path);
+		if target.exists() {
+			return Err(Error::io(target, io::Error::new(io::ErrorKind::AlreadyExists, "The folder already exists")));
+		}
+		
+		let result = self.copy_contents(&workspace, copy_projects)
+			.and_then(|_| workspace.add_to_workspace_list());
+		
+		if let Err(e) = result {
+			let _ = fs::remove_dir_all(target);
+			return Err(e);
+		}
 		
-		workspaces.save()
-	} 
+		Ok(workspace)
+	}
 	
 	
-	/// Sets this workspace as the active one
-	pub fn set_active(&self) -> Result<(), String> {
-		// Deserialize list
-		let mut workspaces = match WorkspaceList::get() {
-			Ok(workspace) => workspace,
-			Err(e) => return Err(e),
-		};
+	/// Copies the project database, the workspace's templates and optionally the projects to another
+	/// workspace's folder. The trash and locks are not copied
+	fn copy_contents(&self, workspace: &Workspace, copy_projects: bool) -> Result<()> {
+		let preferences = PathBuf::from(workspace.workspace_preferences_folder_path());
+		fs::create_dir_all(&preferences).map_err(|e| Error::io(&preferences, e))?;
+		
+		self.get_project_list()?.save(&workspace.project_database_path())?;
 		
-		workspaces.current = self.name.clone();
+		let templates = PathBuf::from(self.workspace_preferences_folder_path()).join(WORKSPACE_TEMPLATES_FOLDER_NAME);
+		if templates.is_dir() {
+			files::copy_folder(&templates, &preferences.join(WORKSPACE_TEMPLATES_FOLDER_NAME))?;
+		}
 		
-		workspaces.save()
+		let source = self.projects_folder_path();
+		if copy_projects && source.exists() {
+			return files::copy_folder(&source, &workspace.projects_folder_path());
+		}
+		
+		let project_list = workspace.get_project_list()?;
+		for name in project_list.get_project_names() {
+			let path = workspace.project_folder(&name);
+			fs::create_dir_all(&path).map_err(|e| Error::io(&path, e))?;
+		}
+		
+		Ok(())
 	}
 	
 	
-	/// Returns the name of this workspace
-	pub fn name<'a>(&'a self) -> &'a str {
-		&self.name
+	/// Sets the command used to open a project
+	pub fn set_project_editor(&mut self, name: &str, editor: Option<String>) -> Result<()> {
+		self.edit_project(name, |project| project.set_editor(editor)).map(|_| ())
 	}
 	
 	
-	fn create_preferences(&self) -> Result<(), String> {
-		/// Create the '.workspace' folder within the workspace root
+	/// Applies a modification to a project and saves the project database, returning the updated project
+	pub fn edit_project<F>(&mut self, name: &str, edit: F) -> Result<Project>
+		where F: FnOnce(&mut Project) {
+		ProjectList::update(&self.project_database_path(), |project_list| {
+			let project = project_list.find_mut(name)?;
+			edit(project);
+			Ok(project.clone())
+		})
+	}
+	
+	
+	/// Returns the command used to open a project: the project's own editor, or else the workspace's
+	/// editor, or else the global default editor
+	pub fn get_project_editor(&self, name: &str) -> Result<Option<String>> {
+		let project_list = self.get_project_list()?;
+		
+		if let Some(editor) = project_list.find(name)?.editor() {
+			return Ok(Some(editor.to_owned()));
+		}
+		
+		if let Some(ref editor) = self.editor {
+			return Ok(Some(editor.clone()));
+		}
+		
+		Ok(Settings::get()?.editor().map(|editor| editor.to_owned()))
+	}
+	
+	
+	pub(crate) fn create_preferences(&self) -> Result<()> {
+		// Create the '.workspace' folder within the workspace root
 		let mut path = PathBuf::from(&self.path);
 		path.push(".workspace");
 		
 		if let Err(e) = fs::create_dir(&path) {
 			if e.kind() != ErrorKind::AlreadyExists {
-				return Err("Failed to create workspace preference folder!".to_owned());
+				return Err(Error::io(path, e));
 			}
 		}
 		
@@ -99,81 +312,418 @@ impl Workspace {
 	}
 	
 	
-	/// Adds a project to this workspace 
-	pub fn add_project(&mut self, project: Project) -> Result<(), String> {
-		let mut project_list = self.get_project_list()?;
+	/// Adds a project to this workspace, optionally filling the new project's folder from a template
+	pub fn add_project(&mut self, project: Project, template: Option<&Template>) -> Result<()> {
+		ProjectList::update(&self.project_database_path(), |project_list| {
+			let names = project.name_chain();
+			let full_name = names.join(":");
+			project_list.add(project.clone())?;
+			
+			let path = self.project_folder(&project_list.canonical_name(&full_name)?);
+			let existed = path.exists();
+			
+			// Folders created here are removed again if the template can not be instantiated
+			create_parent_folders(&path, |path| {
+				fs::create_dir_all(path).map_err(|e| Error::io(path, e))?;
+				
+				let template = match template {
+					Some(template) => template,
+					None => return Ok(()),
+				};
+				
+				let now = time::now();
+				let mut variables = TemplateVariables::new();
+				variables.set("name", names[names.len() - 1]);
+				variables.set("project", &full_name);
+				variables.set("workspace", &self.name);
+				variables.set("date", &time::format_date(now));
+				variables.set("year", &time::year(now).to_string());
+				
+				let result = template.instantiate(path, &variables);
+				if result.is_err() && !existed {
+					let _ = fs::remove_dir_all(path);
+				}
+				result
+			})
+		})
+	}
+	
+	
+	/// Returns the template with a name, templates in this workspace's template folder take
+	/// precedence over the global ones
+	pub fn get_template(&self, name: &str) -> Result<Template> {
+		template::check_name(name)?;
 		
-		if let Err(e) = project_list.add(project.clone()) {
-			return Err(e);
-		}
+		let mut path = PathBuf::from(self.workspace_preferences_folder_path());
+		path.push(WORKSPACE_TEMPLATES_FOLDER_NAME);
+		path.push(name);
 		
-		let mut path = PathBuf::from(&self.path);
-		path.push(WORKSPACE_PROJECTS_FOLDER_NAME);
-		if let Err(e) = project.create_folder(path) {
-			return Err(e);
+		if path.is_dir() {
+			return Template::from_path(name, path);
 		}
 		
-		project_list.save(&self.project_database_path())
+		TemplateList::get()?.lookup(name)
 	}
 	
-	/// Removes a project from this workspace 
-	pub fn remove_project(&mut self, name: &str) -> Result<(), String> {
-		let mut project_list = self.get_project_list()?;
-		
-		if let Err(e) = project_list.remove_project(name) {
-			return Err(e);
+	/// Removes a project from this workspace. The project's folder is either left in place or moved
+	/// to the workspace's trash, from where it can be restored
+	pub fn remove_project(&mut self, name: &str, delete_files: bool) -> Result<()> {
+		ProjectList::update(&self.project_database_path(), |project_list| {
+			let name = project_list.canonical_name(name)?;
+			let project = project_list.remove_project(&name)?;
+			
+			if !delete_files {
+				return Ok(());
+			}
+			
+			let trash_path = self.trash_folder_path();
+			fs::create_dir_all(&trash_path).map_err(|e| Error::io(&trash_path, e))?;
+			
+			Trash::update(&trash_path.join(WORKSPACE_TRASH_MANIFEST_NAME), |trash| {
+				let entry = trash.add(&name, project);
+				
+				let source = self.project_folder(&name);
+				if fs::symlink_metadata(&source).is_ok() {
+					files::move_folder(&source, &trash_path.join(entry.id()))?;
+				}
+				
+				Ok(())
+			})
+		})
+	}
+	
+	
+	/// Returns the projects in this workspace's trash
+	pub fn get_trash(&self) -> Result<Trash> {
+		Trash::get(&self.trash_folder_path().join(WORKSPACE_TRASH_MANIFEST_NAME))
+	}
+	
+	
+	/// Moves a project out of the trash to where it was before it was removed, returning its name
+	pub fn restore_project(&mut self, name: &str) -> Result<String> {
+		use std::io;
+		
+		let trash_path = self.trash_folder_path();
+		
+		ProjectList::update(&self.project_database_path(), |project_list| {
+			Trash::update(&trash_path.join(WORKSPACE_TRASH_MANIFEST_NAME), |trash| {
+				let entry = trash.take(name)?;
+				project_list.insert(entry.name(), entry.project().clone())?;
+				
+				let source = trash_path.join(entry.id());
+				if fs::symlink_metadata(&source).is_ok() {
+					let target = self.project_folder(entry.name());
+					if fs::symlink_metadata(&target).is_ok() {
+						return Err(Error::io(&target, io::Error::new(io::ErrorKind::AlreadyExists, "Project folder already exists")));
+					}
+					
+					if let Some(parent) = target.parent() {
+						fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+					}
+					files::move_folder(&source, &target)?;
+				}
+				
+				Ok(entry.name().to_owned())
+			})
+		})
+	}
+	
+	
+	/// Permanently deletes all projects in the trash, returning the number of deleted projects
+	pub fn empty_trash(&mut self) -> Result<usize> {
+		let trash_path = self.trash_folder_path();
+		if !trash_path.exists() {
+			return Ok(0);
 		}
 		
-		project_list.save(&self.project_database_path())
+		Trash::update(&trash_path.join(WORKSPACE_TRASH_MANIFEST_NAME), |trash| {
+			let entries = trash.clear();
+			
+			for entry in entries.iter() {
+				let path = trash_path.join(entry.id());
+				if let Ok(metadata) = fs::symlink_metadata(&path) {
+					// Only remove the link to projects which were linked into the workspace
+					let result = if metadata.file_type().is_symlink() {
+						fs::remove_file(&path)
+					} else {
+						fs::remove_dir_all(&path)
+					};
+					result.map_err(|e| Error::io(&path, e))?;
+				}
+			}
+			
+			Ok(entries.len())
+		})
 	}
 	
 	
 	/// Return the list of projects for a workspace
-	pub fn get_project_list(&self) -> Result<ProjectList, String> {
+	pub fn get_project_list(&self) -> Result<ProjectList> {
 		let project_database_path = self.project_database_path();
 		
 		// Deserialize list
-		match ProjectList::get(&project_database_path) {
-			Ok(project_list) => Ok(project_list),
-			Err(e) => Err(e),
-		}
+		ProjectList::get(&project_database_path)
 	}
 	
 	
 	/// Return the absolute path to a project with a name
-	pub fn get_project_path(&self, name: &str) -> Result<String, String> {
-		use std::path::MAIN_SEPARATOR;
+	pub fn get_project_path(&self, name: &str) -> Result<String> {
+		use std::io;
 		
 		let project_list = self.get_project_list()?;
 		
-		if let Err(e) = project_list.exists(name) {
-			return Err(e);
+		let path = self.project_folder(&project_list.canonical_name(name)?);
+		
+		if !path.exists() {
+			return Err(Error::io(path, io::Error::new(io::ErrorKind::NotFound, "Project folder does not exist")));
 		}
 		
-		let mut path = PathBuf::from(&self.path);
-		path.push(WORKSPACE_PROJECTS_FOLDER_NAME);
-		path.push(&name.to_lowercase().replace(|c|{c == ':'}, &MAIN_SEPARATOR.to_string()));
+		Ok(path.to_str().unwrap().to_owned())
+	}
+	
+	
+	/// Renames a project or moves it to another parent project, together with its folder. Missing
+	/// parent projects are created. Returns the new name of the project
+	pub fn move_project(&mut self, from: &str, to: &str) -> Result<String> {
+		use std::io;
 		
+		let to = to.replace(|c|{c == '/' || c == '\\'}, ":");
+		project::check_name(&to)?;
+		
+		let (from, to) = ProjectList::update(&self.project_database_path(), |project_list| {
+			let from = project_list.canonical_name(from)?;
+			
+			let lowercase_from = from.to_lowercase();
+			let lowercase_to = to.to_lowercase();
+			if lowercase_to.starts_with(&(lowercase_from.clone() + ":")) {
+				return Err(Error::Invalid(format!("Can not move '{}' into itself", from)));
+			}
+			
+			// Changing the case of a name is the only way to move a project onto itself
+			if lowercase_to != lowercase_from && project_list.exists(&to).is_ok() {
+				return Err(Error::already_exists("project", &to));
+			}
+			
+			let mut project = project_list.remove_project(&from)?;
+			project.set_name(to.rsplit(':').next().unwrap());
+			project_list.insert(&to, project)?;
+			let to = project_list.canonical_name(&to)?;
+			
+			let source = self.project_folder(&from);
+			let target = self.project_folder(&to);
+			if fs::symlink_metadata(&source).is_err() {
+				return Ok((from, to));
+			}
+			
+			if lowercase_to != lowercase_from && fs::symlink_metadata(&target).is_ok() {
+				return Err(Error::io(&target, io::Error::new(io::ErrorKind::AlreadyExists, "Project folder already exists")));
+			}
+			
+			create_parent_folders(&target, |target| files::move_folder(&source, target))?;
+			
+			Ok((from, to))
+		})?;
+		
+		// The history is only informational, the project has been moved either way
+		let _ = History::update(|history| {
+			history.rename_project(&self.name, &from, &to);
+			Ok(())
+		});
+		
+		Ok(to)
+	}
+	
+	
+	/// Creates a git repository with a default .gitignore in the folder of a project
+	pub fn init_repository(&self, name: &str) -> Result<()> {
+		let project_list = self.get_project_list()?;
+		let path = self.project_folder(&project_list.canonical_name(name)?);
+		
+		git::init(&path)
+	}
+	
+	
+	/// Clones a git repository into the folder of a new project and remembers its url as the
+	/// project's remote. Returns the full name of the new project
+	pub fn clone_project(&mut self, url: &str, name: &str) -> Result<String> {
+		use std::io;
+		
+		let url = &git::absolute_url(url);
+		
+		let name = name.replace(|c|{c == '/' || c == '\\'}, ":");
+		project::check_name(&name)?;
+		
+		ProjectList::update(&self.project_database_path(), |project_list| {
+			project_list.add(Project::from_str(&name))?;
+			let name = project_list.canonical_name(&name)?;
+			project_list.find_mut(&name)?.set_remote(Some(url.to_owned()));
+			
+			let target = self.project_folder(&name);
+			if fs::symlink_metadata(&target).is_ok() {
+				return Err(Error::io(&target, io::Error::new(io::ErrorKind::AlreadyExists, "Project folder already exists")));
+			}
+			
+			create_parent_folders(&target, |target| git::clone(url, target))?;
+			
+			Ok(name)
+		})
+	}
+	
+	
+	/// Registers an existing directory as a project, either by moving it into the workspace or by
+	/// linking to it from the workspace
+	pub fn adopt_project(&mut self, source: &Path, name: &str, link: bool) -> Result<()> {
+		use std::io;
+		
+		if !source.is_dir() {
+			return Err(Error::io(source, io::Error::new(io::ErrorKind::NotFound, "Directory does not exist")));
+		}
+		let source = source.canonicalize().map_err(|e| Error::io(source, e))?;
+		
+		ProjectList::update(&self.project_database_path(), |project_list| {
+			let project = Project::from_str(name);
+			let name = project.name_chain().join(":");
+			project_list.add(project)?;
+			
+			// Existing parents keep the case of their name
+			let target = self.project_folder(&project_list.canonical_name(&name)?);
+			
+			// The directory is already in place
+			if target.canonicalize().ok() == Some(source.clone()) {
+				return Ok(());
+			}
+			
+			if resolve_existing(&target).starts_with(&source) {
+				return Err(Error::Invalid(format!("Can not adopt '{}' into itself", source.display())));
+			}
+			
+			if target.exists() {
+				return Err(Error::io(&target, io::Error::new(io::ErrorKind::AlreadyExists, "Project folder already exists")));
+			}
+			
+			create_parent_folders(&target, |target| if link {
+				files::link_folder(&source, target)
+			} else {
+				files::move_folder(&source, target)
+			})
+		})
+	}
+	
+	
+	/// Registers a folder which already exists in the projects folder
+	pub fn register_project(&mut self, name: &str) -> Result<()> {
+		ProjectList::update(&self.project_database_path(), |project_list| project_list.add(Project::from_str(name)))
+	}
+	
+	
+	/// Returns the names of the folders which do not belong to a project, such as 'music:demos'.
+	/// Folders in the projects folder are checked, and those in the folders of projects which already
+	/// have subprojects. Other projects' folders hold their own files
+	pub fn get_untracked_folders(&self) -> Result<Vec<String>> {
+		let project_list = self.get_project_list()?;
+		let names = project_list.get_project_names();
+		let tracked: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+		
+		let mut untracked = Vec::new();
+		self.find_untracked_folders(&self.projects_folder_path(), None, &tracked, &mut untracked)?;
+		
+		for name in names.iter() {
+			let path = self.project_folder(name);
+			let lowercase = name.to_lowercase() + ":";
+			let has_subprojects = tracked.iter().any(|tracked| tracked.starts_with(&lowercase));
+			
+			let is_symlink = fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.file_type().is_symlink());
+			if !has_subprojects || !path.is_dir() || is_symlink {
+				continue;
+			}
+			
+			self.find_untracked_folders(&path, Some(name), &tracked, &mut untracked)?;
+		}
+		
+		untracked.sort();
+		Ok(untracked)
+	}
+	
+	
+	/// Adds the full names of the folders in a project's folder, or the projects folder, which do not
+	/// belong to a project
+	fn find_untracked_folders(&self, path: &Path, parent: Option<&str>, tracked: &[String], untracked: &mut Vec<String>) -> Result<()> {
 		if !path.exists() {
-			return Err(format!("Project folder does not exist!\nProject folder not found in: '{}' ", path.to_str().unwrap()).to_string());
+			return Ok(());
 		}
 		
-		Ok(path.to_str().unwrap().to_owned())
+		for entry in fs::read_dir(path).map_err(|e| Error::io(path, e))?.filter_map(|entry| entry.ok()) {
+			let folder = entry.file_name().to_string_lossy().into_owned();
+			let name = match parent {
+				Some(parent) => format!("{}:{}", parent, folder),
+				None => folder.clone(),
+			};
+			
+			if entry.path().is_dir() && !folder.starts_with('.') && !tracked.contains(&name.to_lowercase()) {
+				untracked.push(name);
+			}
+		}
+		
+		Ok(())
+	}
+	
+	
+	/// Returns the names of the projects whose folder does not exist
+	pub fn get_missing_project_folders(&self) -> Result<Vec<String>> {
+		let project_list = self.get_project_list()?;
+		
+		let mut missing: Vec<String> = project_list.get_project_names().into_iter()
+			.filter(|name| !self.project_folder(name).is_dir())
+			.collect();
+		
+		missing.sort();
+		Ok(missing)
+	}
+	
+	
+	/// Creates the folder of a project if it does not exist
+	pub fn create_project_folder(&self, name: &str) -> Result<()> {
+		let project_list = self.get_project_list()?;
+		let path = self.project_folder(&project_list.canonical_name(name)?);
+		
+		fs::create_dir_all(&path).map_err(|e| Error::io(&path, e))
+	}
+	
+	
+	/// Return the path to the folder containing removed projects
+	fn trash_folder_path(&self) -> PathBuf {
+		let mut path = PathBuf::from(self.workspace_preferences_folder_path());
+		path.push(WORKSPACE_TRASH_FOLDER_NAME);
+		path
+	}
+	
+	
+	/// Return the path to the folder containing all projects
+	fn projects_folder_path(&self) -> PathBuf {
+		let mut path = PathBuf::from(&self.path);
+		path.push(WORKSPACE_PROJECTS_FOLDER_NAME);
+		path
+	}
+	
+	
+	/// Return the path to the folder of a project, given its full name as stored in the project
+	/// list. The folder may not exist
+	pub fn project_folder(&self, name: &str) -> PathBuf {
+		let mut path = self.projects_folder_path();
+		for part in name.split(':') {
+			path.push(part);
+		}
+		path
 	}
 	
 	
 	/// Creates the preference folder for a workspace
-	fn create_project_database(&self, mut path: PathBuf) -> Result<(), String> {
+	fn create_project_database(&self, mut path: PathBuf) -> Result<()> {
 		path.push(WORKSPACE_PROJECT_DATABASE_NAME);
 		
 		if !Path::new(&path).exists() {
-			match fs::File::create(&path) {
-				Ok(mut file) => match file.write_all(b"{ \"projects\": [] }") {
-					Ok(_) => (),
-					Err(_) => return Err("Failed to write to project databse!".to_owned())
-				}
-				Err(_) => return Err("Failed to create project database!".to_owned()),
-			}
+			let mut file = fs::File::create(&path).map_err(|e| Error::io(&path, e))?;
+			file.write_all(b"{ \"projects\": [] }").map_err(|e| Error::io(&path, e))?;
 		}
 		
 		Ok(())
@@ -187,13 +737,56 @@ impl Workspace {
 	}
 	
 	/// Return the path to the project preferences
-	fn project_database_path(&self) -> String {
+	pub(crate) fn project_database_path(&self) -> String {
 		use std::path::MAIN_SEPARATOR;
 		self.workspace_preferences_folder_path() + &MAIN_SEPARATOR.to_string() + WORKSPACE_PROJECT_DATABASE_NAME
 	}
 }
 
 
+/// Returns a path with its longest existing part resolved, so it can be compared with canonical
+/// paths before it exists
+fn resolve_existing(path: &Path) -> PathBuf {
+	for ancestor in path.ancestors() {
+		if let (Ok(resolved), Ok(rest)) = (ancestor.canonicalize(), path.strip_prefix(ancestor)) {
+			return resolved.join(rest);
+		}
+	}
+	
+	path.to_path_buf()
+}
+
+
+/// Creates the missing parent folders of a path and calls a function that creates the path itself.
+/// The parent folders are removed again if the function fails
+fn create_parent_folders<F>(path: &Path, create: F) -> Result<()>
+	where F: FnOnce(&Path) -> Result<()> {
+	let mut created = Vec::new();
+	let mut parent = path.parent();
+	while let Some(folder) = parent {
+		if folder.exists() {
+			break;
+		}
+		created.push(folder.to_path_buf());
+		parent = folder.parent();
+	}
+	
+	let result = match created.first() {
+		Some(folder) => fs::create_dir_all(folder).map_err(|e| Error::io(folder, e)),
+		None => Ok(()),
+	}.and_then(|_| create(path));
+	
+	if result.is_err() {
+		for folder in created.iter() {
+			let _ = fs::remove_dir(folder);
+		}
+	}
+	
+	result
+}
+
+
+/// The global list of workspaces and the currently active one, stored in 'workspaces.json'
 #[derive(Serialize, Deserialize)]
 pub struct WorkspaceList {
 	#[serde(default = "default_workspaces")]
@@ -216,97 +809,102 @@ fn default_workspaces() -> Vec<Workspace> {
 
 impl WorkspaceList {
 	/// Load the current workspace list from a file
-	pub fn get() -> Result<WorkspaceList, String> {
+	pub fn get() -> Result<WorkspaceList> {
 		let workspace_file_path = WorkspaceList::path()?;
 		
 		// Add file if it doesn't exist
-		if !Path::new(&workspace_file_path).exists() {
-			match fs::File::create(&workspace_file_path) {
-				Ok(mut file) => match file.write_all(b"{\"workspaces\": [], \"current\": \"\"}") {
-					Ok(_) => (),
-					Err(_) => return Err("Failed to write to workspace list file!".to_owned())
-				}
-				Err(_) => return Err("Failed to create workspace list file!".to_owned()),
-			}
-		}
-		
-		let mut workspace_file = String::new();
-		match fs::File::open(&workspace_file_path) {
-			Ok(mut file) => match file.read_to_string(&mut workspace_file) {
-				Ok(_) => (),
-				Err(_) => return Err("Failed to write to workspace list file!".to_owned())
-			}
-			Err(_) => return Err("Failed to open workspace list file!".to_owned()),
+		if !database::exists(Path::new(&workspace_file_path)) {
+			let mut file = fs::File::create(&workspace_file_path).map_err(|e| Error::io(&workspace_file_path, e))?;
+			file.write_all(b"{\"workspaces\": [], \"current\": \"\"}").map_err(|e| Error::io(&workspace_file_path, e))?;
 		}
 		
 		// Deserialize list
-		let workspaces: WorkspaceList = match serde_json::from_str(&workspace_file){
-			Ok(workspaces) => workspaces,
-			Err(_) => return Err("Failed to load workspace list!".to_owned()),
-		};
-		
-		Ok(workspaces)
+		database::load(Path::new(&workspace_file_path))
 	}
 	
-
+	
 	/// Write the current workspace list to a file
-	pub fn save(&self) -> Result<(), String> {
+	pub fn save(&self) -> Result<()> {
 		let workspace_file_path = WorkspaceList::path()?;
 		
-		// Reseralize list
-		let workspace_file = serde_json::to_string_pretty(self).unwrap();
+		database::save(Path::new(&workspace_file_path), self)
+	}
+	
+	
+	/// Loads the workspace list, applies a modification and saves it again. Other processes can
+	/// not modify the list in the meantime
+	pub fn update<T, F>(modify: F) -> Result<T>
+		where F: FnOnce(&mut WorkspaceList) -> Result<T> {
+		let _lock = database::lock(Path::new(&WorkspaceList::path()?))?;
 		
-		match fs::File::create(&workspace_file_path) {
-			Ok(mut file) => match file.write_all(workspace_file.as_bytes()) {
-				Ok(_) => (),
-				Err(_) => return Err("Failed to write to workspace list file!".to_owned())
-			}
-			Err(_) => return Err("Failed to create/open workspace list file!".to_owned()),
-		}
+		let mut workspaces = WorkspaceList::get()?;
+		let result = modify(&mut workspaces)?;
+		workspaces.save()?;
 		
-		Ok(())
+		Ok(result)
 	}
 	
 	
-	// Looks up a workspace from existing workspaces
-	pub fn lookup(&self, name: &str) -> Result<Workspace, String> {
+	/// Looks up a workspace from existing workspaces
+	pub fn lookup(&self, name: &str) -> Result<Workspace> {
+		let name = name.to_lowercase();
 		if let Some(workspace) = self.workspaces.iter().find(|elem|{ elem.name.to_lowercase() == name }) {
-			return Ok(workspace.clone());
+			Ok(workspace.clone())
 		} else {
-			return Err(format!("No workspace with the name '{}'!", name).to_owned());
+			Err(Error::not_found("workspace", &name))
 		}
 	}
 	
 	
-	// Looks up a workspace's index from existing workspaces
-	pub fn lookup_index(&self, name: &str) -> Result<usize, String> {
+	/// Looks up a workspace's index from existing workspaces
+	pub fn lookup_index(&self, name: &str) -> Result<usize> {
 		let name = name.to_lowercase();
 		if let Some(index) = self.workspaces.iter().position(|elem|{ elem.name.to_lowercase() == name }) {
-			return Ok(index);
+			Ok(index)
 		} else {
-			return Err(format!("No workspace with the name '{}'!", name).to_owned());
+			Err(Error::not_found("workspace", &name))
 		}
 	}
 	
 	
-	// Looks up the current workspace
-	pub fn current(&self) -> Result<Workspace, String> {
+	/// Returns all workspaces in the list
+	pub fn workspaces(&self) -> &[Workspace] {
+		&self.workspaces
+	}
+	
+	
+	/// Returns true if a workspace is the active one
+	pub fn is_current(&self, workspace: &Workspace) -> bool {
+		self.current.to_lowercase() == workspace.name.to_lowercase()
+	}
+	
+	
+	/// Returns the name of the active workspace, which is empty if none has been selected
+	pub fn current_name(&self) -> &str {
+		&self.current
+	}
+	
+	
+	/// Deselects the active workspace
+	pub fn clear_current(&mut self) {
+		self.current = "".to_owned();
+	}
+	
+	
+	/// Looks up the current workspace
+	pub fn current(&self) -> Result<Workspace> {
 		if let Ok(workspace) = self.lookup(&self.current) {
-			return Ok(workspace.clone());
+			Ok(workspace)
 		} else {
-			return Err("No workspace currently selected!".to_owned());
+			Err(Error::Invalid("No workspace currently selected!".to_owned()))
 		}
 	}
 	
 	
-	/// Remves a workspace from the list, optionally removes the directory aswell
-	pub fn remove(&mut self, name: &str) -> Result<(), String> {
-		match self.lookup_index(name) {
-			Ok(index) => {
-				self.workspaces.remove(index);
-			},
-			Err(e) => return Err(e),
-		}
+	/// Removes a workspace from the list, its folder is left untouched
+	pub fn remove(&mut self, name: &str) -> Result<()> {
+		let index = self.lookup_index(name)?;
+		self.workspaces.remove(index);
 		
 		if self.current.to_lowercase() == name.to_lowercase() {
 			self.current = "".to_owned();
@@ -317,17 +915,53 @@ impl WorkspaceList {
 	
 	
 	/// Return the path to the workspaces file
-	fn path() -> Result<String, String> {
-		use std::env::current_exe;
+	fn path() -> Result<String> {
+		let mut path = data_directory()?;
+		path.push(WORKSPACES_FILE_NAME);
+		Ok(path.to_str().unwrap().to_owned())
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::env;
+	use std::process::{self, Command};
+	
+	
+	/// Returns a new workspace in an empty temporary folder, which is not added to the list of
+	/// workspaces
+	fn workspace(name: &str) -> Workspace {
+		let path = env::temp_dir().join(format!("rpm-test-{}-{}", name, process::id()));
+		let _ = fs::remove_dir_all(&path);
+		fs::create_dir_all(&path).unwrap();
 		
-		match current_exe() {
-			Ok(mut exe_path) => {
-				exe_path.set_file_name(WORKSPACES_FILE_NAME);
-				return Ok(exe_path.to_str().unwrap().to_owned());
-			}
-			Err(_) => {
-				return Err("Failed to get path to 'rpm' executable!".to_owned());
-			}
-		}
+		let workspace = Workspace {
+			name: name.to_owned(),
+			path: path.to_string_lossy().into_owned(),
+			editor: None
+		};
+		workspace.create_preferences().unwrap();
+		workspace
+	}
+	
+	
+	#[test]
+	fn clone_project_clones_a_local_repository() {
+		let mut workspace = workspace("clone");
+		let remote = Path::new(&workspace.path).join("synth.git");
+		let status = Command::new("git").args(["init", "--bare", "--quiet"]).arg(&remote).status().unwrap();
+		assert!(status.success());
+		
+		let name = workspace.clone_project(&remote.to_string_lossy(), "music:synth").unwrap();
+		assert_eq!(name, "music:synth");
+		assert!(workspace.project_folder(&name).join(".git").is_dir());
+		
+		let project_list = workspace.get_project_list().unwrap();
+		let stored = project_list.find(&name).unwrap().remote().unwrap();
+		assert_eq!(Path::new(stored), remote.canonicalize().unwrap());
+		
+		fs::remove_dir_all(&workspace.path).unwrap();
 	}
-}
\ No newline at end of file
+}